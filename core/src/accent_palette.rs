@@ -0,0 +1,322 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use anyhow::Context;
+use palette::{ColorDifference, IntoColor, Lab, Lch, Oklab, Srgb};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::{
+    ase,
+    color::{Accent, Backgrounds, ContrastAlgorithm, WorkingSpace},
+    gpl,
+};
+
+/// An entry of a palette, the colors are (de)serialized as `#rrggbb`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccentPalette {
+    pub name: String,
+    /// the accent as text
+    #[serde(with = "hex_srgb")]
+    pub accent_color: Srgb,
+    #[serde(with = "hex_srgb")]
+    pub accent_color_bg: Srgb,
+    /// the text on the accent background
+    #[serde(with = "hex_srgb")]
+    pub accent_color_fg: Srgb,
+}
+
+impl AccentPalette {
+    /// Creates an entry from its background, deriving the accent as text & the text on the accent
+    /// against `base`, the background of the color scheme
+    pub fn from_bg(
+        name: String,
+        accent_color_bg: Srgb,
+        base: Lch,
+        is_dark: bool,
+        algorithm: ContrastAlgorithm,
+        space: WorkingSpace,
+    ) -> Self {
+        let accent = Accent::derive_roles(
+            accent_color_bg.into_color(),
+            base,
+            is_dark,
+            algorithm,
+            space,
+        );
+        Self {
+            name,
+            accent_color: accent.accent,
+            accent_color_bg,
+            accent_color_fg: accent.accent_fg,
+        }
+    }
+}
+
+impl From<&AccentPalette> for Accent {
+    fn from(p: &AccentPalette) -> Self {
+        Self {
+            accent: p.accent_color,
+            accent_bg: p.accent_color_bg,
+            accent_fg: p.accent_color_fg,
+        }
+    }
+}
+
+/// A named set of accents for the light & dark color schemes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaletteSet {
+    /// the file stem, used to refer to the set
+    #[serde(skip)]
+    pub id: String,
+    pub name: String,
+    pub light: Vec<AccentPalette>,
+    pub dark: Vec<AccentPalette>,
+}
+
+impl PaletteSet {
+    /// Parses a palette set from TOML or JSON, depending on the extension of `file_name`
+    pub fn parse(file_name: &str, data: &str) -> anyhow::Result<Self> {
+        let path = Path::new(file_name);
+        let mut set: Self = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(data)?,
+            Some("json") => serde_json::from_str(data)?,
+            _ => anyhow::bail!("Unsupported palette format, expected .toml or .json"),
+        };
+        set.id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+        Ok(set)
+    }
+
+    /// Reads & parses a `.toml` or `.json` palette set
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let data = fs::read_to_string(path)?;
+        Self::parse(&path.to_string_lossy(), &data)
+            .with_context(|| format!("Failed to parse palette {}", path.display()))
+    }
+
+    /// The entries for the light or dark color scheme
+    pub fn palette(&self, is_dark: bool) -> &[AccentPalette] {
+        if is_dark {
+            &self.dark
+        } else {
+            &self.light
+        }
+    }
+
+    pub fn palette_mut(&mut self, is_dark: bool) -> &mut Vec<AccentPalette> {
+        if is_dark {
+            &mut self.dark
+        } else {
+            &mut self.light
+        }
+    }
+}
+
+/// Parses a palette set from a `.gpl`, `.ase`, `.toml` or `.json` file
+/// roles missing from the file are derived against the light & dark `backgrounds` with `algorithm` in `space`
+pub fn import(
+    file_name: &str,
+    data: &[u8],
+    backgrounds: &[Backgrounds; 2],
+    algorithm: ContrastAlgorithm,
+    space: WorkingSpace,
+) -> anyhow::Result<PaletteSet> {
+    let set = match Path::new(file_name).extension().and_then(|e| e.to_str()) {
+        Some("gpl") => gpl::import(
+            file_name,
+            std::str::from_utf8(data)?,
+            backgrounds,
+            algorithm,
+            space,
+        ),
+        Some("ase") => ase::import(file_name, data, backgrounds, algorithm, space),
+        _ => return PaletteSet::parse(file_name, std::str::from_utf8(data)?),
+    };
+    set.with_context(|| format!("Failed to import palette {}", file_name))
+}
+
+/// Serializes a palette set as a `.gpl`, `.ase`, `.toml` or `.json` file, depending on the extension of `file_name`
+/// `.gpl` files only hold the palette of one color scheme
+pub fn export(file_name: &str, set: &PaletteSet, is_dark: bool) -> anyhow::Result<Vec<u8>> {
+    Ok(
+        match Path::new(file_name).extension().and_then(|e| e.to_str()) {
+            Some("gpl") => {
+                let scheme = if is_dark { "Dark" } else { "Light" };
+                gpl::export(&format!("{} ({scheme})", set.name), set.palette(is_dark)).into_bytes()
+            }
+            Some("ase") => ase::export(set),
+            Some("toml") => toml::to_string_pretty(set)?.into_bytes(),
+            Some("json") => serde_json::to_string_pretty(set)?.into_bytes(),
+            _ => anyhow::bail!("Unsupported palette format, expected .gpl, .ase, .toml or .json"),
+        },
+    )
+}
+
+/// (De)serializes colors as `#rrggbb` strings
+mod hex_srgb {
+    use palette::Srgb;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use crate::color;
+
+    pub fn serialize<S: Serializer>(c: &Srgb, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("#{}", color::hex_from_srgb(*c)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Srgb, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        color::srgb_from_hex(&s).map_err(de::Error::custom)
+    }
+}
+
+/// Perceptual distance used to match a color to a palette entry
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Default)]
+pub enum DistanceMetric {
+    /// euclidean distance in CIE Lab
+    Cie76,
+    /// CIE94 with the graphic arts weights
+    Cie94,
+    #[default]
+    Ciede2000,
+    /// euclidean distance in Oklab
+    Oklab,
+}
+
+impl DistanceMetric {
+    /// The distance of `c2` from `c1`, CIE94 isn't symmetric
+    pub fn distance(self, c1: Lch, c2: Lch) -> f32 {
+        match self {
+            Self::Cie76 => {
+                let (c1, c2): (Lab, Lab) = (c1.into_color(), c2.into_color());
+                ((c1.l - c2.l).powi(2) + (c1.a - c2.a).powi(2) + (c1.b - c2.b).powi(2)).sqrt()
+            }
+            Self::Cie94 => {
+                let (k1, k2) = (0.045, 0.015);
+                let (lab1, lab2): (Lab, Lab) = (c1.into_color(), c2.into_color());
+                let dl = c1.l - c2.l;
+                let dc = c1.chroma - c2.chroma;
+                // the hue difference is derived from a & b, so it is correct around 0°/360°
+                let dh_sq =
+                    ((lab1.a - lab2.a).powi(2) + (lab1.b - lab2.b).powi(2) - dc.powi(2)).max(0.0);
+                let sc = 1.0 + k1 * c1.chroma;
+                let sh = 1.0 + k2 * c1.chroma;
+                (dl.powi(2) + (dc / sc).powi(2) + dh_sq / sh.powi(2)).sqrt()
+            }
+            Self::Ciede2000 => {
+                let (c1, c2): (Lab, Lab) = (c1.into_color(), c2.into_color());
+                c1.get_color_difference(&c2)
+            }
+            Self::Oklab => {
+                let (c1, c2): (Oklab, Oklab) = (c1.into_color(), c2.into_color());
+                ((c1.l - c2.l).powi(2) + (c1.a - c2.a).powi(2) + (c1.b - c2.b).powi(2)).sqrt()
+            }
+        }
+    }
+}
+
+/// Finds the index of the palette entry with the background color closest to `color`
+pub fn nearest(palette: &[AccentPalette], color: Lch, metric: DistanceMetric) -> Option<usize> {
+    palette
+        .iter()
+        .map(|p| metric.distance(color, p.accent_color_bg.into_color()))
+        .enumerate()
+        .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, c: Lch) -> AccentPalette {
+        let c: Srgb = c.into_color();
+        AccentPalette {
+            name: name.into(),
+            accent_color: c,
+            accent_color_bg: c,
+            accent_color_fg: Srgb::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    fn reds_palette() -> Vec<AccentPalette> {
+        vec![
+            entry("BLUE", Lch::new(50.0, 60.0, 270.0)),
+            entry("RED", Lch::new(50.0, 60.0, 5.0)),
+            entry("PURPLE", Lch::new(50.0, 60.0, 315.0)),
+            entry("ORANGE", Lch::new(60.0, 60.0, 55.0)),
+        ]
+    }
+
+    const METRICS: [DistanceMetric; 4] = [
+        DistanceMetric::Cie76,
+        DistanceMetric::Cie94,
+        DistanceMetric::Ciede2000,
+        DistanceMetric::Oklab,
+    ];
+
+    #[test]
+    fn nearest_wraps_around_hue_seam() {
+        let palette = reds_palette();
+        for metric in METRICS {
+            assert_eq!(
+                nearest(&palette, Lch::new(50.0, 60.0, 355.0), metric),
+                Some(1),
+                "{:?}",
+                metric
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_wraps_around_hue_seam_reversed() {
+        let palette = vec![
+            entry("RED", Lch::new(50.0, 60.0, 355.0)),
+            entry("ORANGE", Lch::new(60.0, 60.0, 55.0)),
+            entry("PURPLE", Lch::new(50.0, 60.0, 315.0)),
+        ];
+        for metric in METRICS {
+            assert_eq!(
+                nearest(&palette, Lch::new(50.0, 60.0, 5.0), metric),
+                Some(0),
+                "{:?}",
+                metric
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_exact_match() {
+        let palette = reds_palette();
+        for metric in METRICS {
+            for (i, p) in palette.iter().enumerate() {
+                let c: Lch = p.accent_color_bg.into_color();
+                assert_eq!(nearest(&palette, c, metric), Some(i), "{:?}", metric);
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_empty_palette() {
+        assert_eq!(
+            nearest(&[], Lch::new(50.0, 60.0, 5.0), DistanceMetric::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn distance_is_symmetric_across_seam() {
+        let (c1, c2) = (Lch::new(50.0, 60.0, 355.0), Lch::new(50.0, 60.0, 5.0));
+        for metric in METRICS {
+            let d = metric.distance(c1, c2);
+            assert!((d - metric.distance(c2, c1)).abs() < 0.5, "{:?}", metric);
+            assert!(
+                d < metric.distance(c1, Lch::new(50.0, 60.0, 315.0)),
+                "{:?}",
+                metric
+            );
+        }
+    }
+}
@@ -0,0 +1,436 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use palette::{
+    chromatic_adaptation::AdaptInto,
+    convert::IntoColorUnclamped,
+    white_point::{D50, D65},
+    Clamp, Lab, Srgb, Xyz,
+};
+use std::path::Path;
+
+use crate::{
+    accent_palette::{AccentPalette, PaletteSet},
+    color::{Backgrounds, ContrastAlgorithm, WorkingSpace},
+};
+
+const SIGNATURE: &[u8; 4] = b"ASEF";
+const GROUP_START: u16 = 0xc001;
+const GROUP_END: u16 = 0xc002;
+const COLOR_ENTRY: u16 = 0x0001;
+/// the color type of swatches that aren't global or spot colors
+const COLOR_TYPE_NORMAL: u16 = 2;
+/// names of the swatches holding the roles of an exported accent
+const ROLE_BG: &str = "accent_bg_color";
+const ROLE_FG: &str = "accent_fg_color";
+const ROLE_AS_FG: &str = "accent_color";
+
+/// A color of an Adobe Swatch Exchange file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Swatch {
+    pub name: String,
+    pub color: Srgb,
+}
+
+/// Swatches of a group, swatches outside of groups have no group name
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwatchGroup {
+    pub name: Option<String>,
+    pub swatches: Vec<Swatch>,
+}
+
+/// Reads big endian values and reports the offset of truncated data
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + n)
+            .ok_or_else(|| anyhow::anyhow!("Unexpected end of file at byte {}", self.offset))?;
+        self.offset += n;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> anyhow::Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn f32(&mut self) -> anyhow::Result<f32> {
+        Ok(f32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    /// UTF-16 string prefixed with its length, including the null terminator
+    fn name(&mut self) -> anyhow::Result<String> {
+        let len = self.u16()? as usize;
+        let units: Vec<u16> = self
+            .take(len * 2)?
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .take_while(|u| *u != 0)
+            .collect();
+        Ok(String::from_utf16_lossy(&units))
+    }
+}
+
+/// Parses the swatches of an ASE file
+/// RGB, LAB & gray swatches are converted to sRGB, CMYK swatches are skipped
+pub fn parse(data: &[u8]) -> anyhow::Result<Vec<SwatchGroup>> {
+    let mut reader = Reader { data, offset: 0 };
+    if reader.take(4).ok() != Some(SIGNATURE.as_slice()) {
+        anyhow::bail!("Not an Adobe Swatch Exchange file");
+    }
+    let (major, _minor) = (reader.u16()?, reader.u16()?);
+    if major != 1 {
+        anyhow::bail!("Unsupported ASE version {major}");
+    }
+
+    let mut groups: Vec<SwatchGroup> = vec![];
+    let mut in_group = false;
+    for _ in 0..reader.u32()? {
+        let block_type = reader.u16()?;
+        let len = reader.u32()? as usize;
+        let offset = reader.offset;
+        let mut block = Reader {
+            data: reader.take(len)?,
+            offset: 0,
+        };
+        let with_offset = |e: anyhow::Error| e.context(format!("Malformed block at byte {offset}"));
+
+        match block_type {
+            GROUP_START => {
+                groups.push(SwatchGroup {
+                    name: Some(block.name().map_err(with_offset)?),
+                    swatches: vec![],
+                });
+                in_group = true;
+            }
+            GROUP_END => in_group = false,
+            COLOR_ENTRY => {
+                let swatch = match swatch(&mut block).map_err(with_offset)? {
+                    Some(swatch) => swatch,
+                    None => continue,
+                };
+                match groups.last_mut() {
+                    Some(group) if in_group || group.name.is_none() => group.swatches.push(swatch),
+                    _ => groups.push(SwatchGroup {
+                        name: None,
+                        swatches: vec![swatch],
+                    }),
+                }
+            }
+            // unknown blocks can be skipped thanks to their length
+            _ => {}
+        }
+    }
+
+    Ok(groups)
+}
+
+fn swatch(block: &mut Reader) -> anyhow::Result<Option<Swatch>> {
+    let name = block.name()?;
+    let model = block.take(4)?;
+    let c: Srgb = match model {
+        b"RGB " => Srgb::new(block.f32()?, block.f32()?, block.f32()?),
+        b"LAB " => {
+            // L is stored from 0 to 1, Adobe uses D50 for LAB
+            let lab = Lab::<D50>::with_wp(block.f32()? * 100.0, block.f32()?, block.f32()?);
+            let xyz: Xyz<D50> = lab.into_color_unclamped();
+            let xyz: Xyz<D65> = xyz.adapt_into();
+            xyz.into_color_unclamped()
+        }
+        b"Gray" => {
+            let v = block.f32()?;
+            Srgb::new(v, v, v)
+        }
+        b"CMYK" => {
+            log::warn!("Skipping CMYK swatch {:?}, CMYK isn't supported", name);
+            return Ok(None);
+        }
+        _ => anyhow::bail!(
+            "Unknown color model {:?}",
+            String::from_utf8_lossy(model).trim()
+        ),
+    };
+
+    Ok(Some(Swatch {
+        name,
+        color: c.clamp(),
+    }))
+}
+
+/// The color scheme named by a group, `None` for both
+///
+/// Only whole words count, so "Darkorange" isn't dark, & the last one wins, e.g. "Light (dark)"
+fn group_scheme(name: &str) -> Option<bool> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .rev()
+        .find_map(|word| match word.to_lowercase().as_str() {
+            "dark" => Some(true),
+            "light" => Some(false),
+            _ => None,
+        })
+}
+
+fn group_entries(
+    group: &SwatchGroup,
+    backgrounds: &[Backgrounds; 2],
+    is_dark: bool,
+    algorithm: ContrastAlgorithm,
+    space: WorkingSpace,
+) -> Vec<AccentPalette> {
+    let base = backgrounds[is_dark as usize].worst_case(is_dark);
+    let role = |role: &str| {
+        group
+            .swatches
+            .iter()
+            .find(|s| s.name == role)
+            .map(|s| s.color)
+    };
+
+    match (group.name.as_deref(), role(ROLE_BG)) {
+        // an exported accent, the group holds the roles of one entry
+        (Some(name), Some(bg)) => {
+            let name = name
+                .trim_end_matches(" (Light)")
+                .trim_end_matches(" (Dark)");
+            let mut entry =
+                AccentPalette::from_bg(name.into(), bg, base, is_dark, algorithm, space);
+            if let Some(c) = role(ROLE_FG) {
+                entry.accent_color_fg = c;
+            }
+            if let Some(c) = role(ROLE_AS_FG) {
+                entry.accent_color = c;
+            }
+            vec![entry]
+        }
+        _ => group
+            .swatches
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let name = match s.name.as_str() {
+                    "" => format!("COLOR_{}", i + 1),
+                    name => name.to_string(),
+                };
+                AccentPalette::from_bg(name, s.color, base, is_dark, algorithm, space)
+            })
+            .collect(),
+    }
+}
+
+/// Imports an ASE file as a palette set
+/// Groups named light or dark only add entries for that color scheme, the missing roles are derived
+pub fn import(
+    file_name: &str,
+    data: &[u8],
+    backgrounds: &[Backgrounds; 2],
+    algorithm: ContrastAlgorithm,
+    space: WorkingSpace,
+) -> anyhow::Result<PaletteSet> {
+    let mut light = vec![];
+    let mut dark = vec![];
+    for group in parse(data)? {
+        let scheme = group.name.as_deref().and_then(group_scheme);
+        if scheme != Some(true) {
+            light.extend(group_entries(&group, backgrounds, false, algorithm, space));
+        }
+        if scheme != Some(false) {
+            dark.extend(group_entries(&group, backgrounds, true, algorithm, space));
+        }
+    }
+    if light.is_empty() && dark.is_empty() {
+        anyhow::bail!("The file doesn't contain any RGB, LAB or gray swatches");
+    }
+
+    // derive the missing color scheme from the other one
+    let rederive = |entries: &[AccentPalette], is_dark: bool| {
+        let base = backgrounds[is_dark as usize].worst_case(is_dark);
+        entries
+            .iter()
+            .map(|e| {
+                AccentPalette::from_bg(
+                    e.name.clone(),
+                    e.accent_color_bg,
+                    base,
+                    is_dark,
+                    algorithm,
+                    space,
+                )
+            })
+            .collect()
+    };
+    if light.is_empty() {
+        light = rederive(&dark, false);
+    } else if dark.is_empty() {
+        dark = rederive(&light, true);
+    }
+
+    let id = Path::new(file_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string();
+    Ok(PaletteSet {
+        name: id.clone(),
+        id,
+        light,
+        dark,
+    })
+}
+
+/// Writes the entries of both color schemes as ASE groups holding the roles of an accent
+pub fn export(set: &PaletteSet) -> Vec<u8> {
+    let mut blocks = vec![];
+    for (is_dark, scheme) in [(false, "Light"), (true, "Dark")] {
+        for entry in set.palette(is_dark) {
+            blocks.push((GROUP_START, name(&format!("{} ({scheme})", entry.name))));
+            for (role, c) in [
+                (ROLE_BG, entry.accent_color_bg),
+                (ROLE_FG, entry.accent_color_fg),
+                (ROLE_AS_FG, entry.accent_color),
+            ] {
+                let mut data = name(role);
+                data.extend(b"RGB ");
+                for v in [c.red, c.green, c.blue] {
+                    data.extend(v.to_be_bytes());
+                }
+                data.extend(COLOR_TYPE_NORMAL.to_be_bytes());
+                blocks.push((COLOR_ENTRY, data));
+            }
+            blocks.push((GROUP_END, vec![]));
+        }
+    }
+
+    let mut ase = SIGNATURE.to_vec();
+    ase.extend(1u16.to_be_bytes());
+    ase.extend(0u16.to_be_bytes());
+    ase.extend((blocks.len() as u32).to_be_bytes());
+    for (block_type, data) in blocks {
+        ase.extend(block_type.to_be_bytes());
+        ase.extend((data.len() as u32).to_be_bytes());
+        ase.extend(data);
+    }
+    ase
+}
+
+fn name(s: &str) -> Vec<u8> {
+    let units: Vec<u16> = s.encode_utf16().chain([0]).collect();
+    let mut data = (units.len() as u16).to_be_bytes().to_vec();
+    data.extend(units.iter().flat_map(|u| u.to_be_bytes()));
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color;
+
+    fn color_block(name: &str, model: &[u8; 4], values: &[f32]) -> Vec<u8> {
+        let mut data = super::name(name);
+        data.extend(model);
+        for v in values {
+            data.extend(v.to_be_bytes());
+        }
+        data.extend(COLOR_TYPE_NORMAL.to_be_bytes());
+
+        let mut block = COLOR_ENTRY.to_be_bytes().to_vec();
+        block.extend((data.len() as u32).to_be_bytes());
+        block.extend(data);
+        block
+    }
+
+    fn ase(blocks: &[Vec<u8>]) -> Vec<u8> {
+        let mut ase = SIGNATURE.to_vec();
+        ase.extend([0, 1, 0, 0]);
+        ase.extend((blocks.len() as u32).to_be_bytes());
+        ase.extend(blocks.concat());
+        ase
+    }
+
+    #[test]
+    fn parse_converts_color_models() {
+        let data = ase(&[
+            color_block("Blue", b"RGB ", &[0.208, 0.518, 0.894]),
+            color_block("White", b"LAB ", &[1.0, 0.0, 0.0]),
+            color_block("Cyan", b"CMYK", &[1.0, 0.0, 0.0, 0.0]),
+            color_block("Gray", b"Gray", &[0.5]),
+        ]);
+        let groups = parse(&data).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].name, None);
+
+        let swatches = &groups[0].swatches;
+        let hex: Vec<_> = swatches
+            .iter()
+            .map(|s| (s.name.as_str(), color::hex_from_srgb(s.color)))
+            .collect();
+        assert_eq!(
+            hex,
+            [("Blue", "3584e4"), ("White", "ffffff"), ("Gray", "808080")]
+                .map(|(n, h)| (n, h.to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_reports_truncated_file() {
+        let mut data = ase(&[color_block("Blue", b"RGB ", &[0.2, 0.5, 0.9])]);
+        data.truncate(data.len() - 3);
+        let err = parse(&data).unwrap_err();
+        assert!(err.to_string().contains("Unexpected end of file"));
+
+        assert!(parse(b"GIMP Palette").is_err());
+    }
+
+    #[test]
+    fn group_scheme_matches_whole_words() {
+        assert_eq!(group_scheme("Accents (dark)"), Some(true));
+        assert_eq!(group_scheme("Light accents"), Some(false));
+        assert_eq!(group_scheme("Light-Theme (Dark)"), Some(true));
+        assert_eq!(group_scheme("Darkorange"), None);
+        assert_eq!(group_scheme("Highlights"), None);
+        assert_eq!(group_scheme("Accents"), None);
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let entry = |bg: &str| AccentPalette {
+            name: "BLUE".into(),
+            accent_color: color::srgb_from_hex("#1c71d8").unwrap(),
+            accent_color_bg: color::srgb_from_hex(bg).unwrap(),
+            accent_color_fg: color::srgb_from_hex("#ffffff").unwrap(),
+        };
+        let set = PaletteSet {
+            id: "brand".into(),
+            name: "Brand".into(),
+            light: vec![entry("#3584e4")],
+            dark: vec![entry("#1a5fb4")],
+        };
+        let imported = import(
+            "brand.ase",
+            &export(&set),
+            &[Backgrounds::adwaita(false), Backgrounds::adwaita(true)],
+            ContrastAlgorithm::Wcag2,
+            WorkingSpace::CieLch,
+        )
+        .unwrap();
+
+        for (a, b) in [(&imported.light, &set.light), (&imported.dark, &set.dark)] {
+            assert_eq!(a.len(), 1);
+            assert_eq!(a[0].name, b[0].name);
+            for (a, b) in [
+                (a[0].accent_color, b[0].accent_color),
+                (a[0].accent_color_bg, b[0].accent_color_bg),
+                (a[0].accent_color_fg, b[0].accent_color_fg),
+            ] {
+                assert_eq!(color::hex_from_srgb(a), color::hex_from_srgb(b));
+            }
+        }
+    }
+}
@@ -0,0 +1,1112 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use hex::encode;
+use palette::{
+    convert::IntoColorUnclamped, white_point::D65, Clamp, FromColor, IntoColor, Lch, Oklab, Oklch,
+    Pixel, RelativeContrast, Srgb, Xyz,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+
+/// Formats `c` as `rrggbb`, without the leading `#`
+pub fn hex_from_srgb(c: Srgb) -> String {
+    encode::<[u8; 3]>(Srgb::into_raw(c.into_format()))
+}
+
+/// Parses `#rgb` or `#rrggbb`, the leading `#` is optional
+pub fn srgb_from_hex(s: &str) -> anyhow::Result<Srgb> {
+    let hex = s.trim().trim_start_matches('#');
+    let hex = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 => hex.to_string(),
+        _ => anyhow::bail!("Invalid hex color {:?}, expected #rgb or #rrggbb", s),
+    };
+    let mut rgb = [0; 3];
+    hex::decode_to_slice(&hex, &mut rgb)
+        .map_err(|e| anyhow::anyhow!("Invalid hex color {:?}, {}", s, e))?;
+    Ok(Srgb::<u8>::from_raw(&rgb).into_format())
+}
+
+/// The colors of an accent, as the `@define-color` names of libadwaita
+#[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Accent {
+    /// `accent_color`, the accent as text on the window & view backgrounds
+    pub accent: Srgb,
+    /// `accent_bg_color`, the background of accented widgets
+    pub accent_bg: Srgb,
+    /// `accent_fg_color`, the text on `accent_bg`
+    pub accent_fg: Srgb,
+}
+
+impl Accent {
+    /// The colors with their `@define-color` names
+    pub fn roles(&self) -> [(&'static str, Srgb); 3] {
+        [
+            ("accent_color", self.accent),
+            ("accent_bg_color", self.accent_bg),
+            ("accent_fg_color", self.accent_fg),
+        ]
+    }
+
+    /// The accent as `@define-color` rules for a GTK style sheet
+    pub fn to_css(&self) -> String {
+        let mut css = String::from("\n");
+        for (name, c) in self.roles() {
+            css += &define_color(name, c);
+            css.push('\n');
+        }
+        css
+    }
+
+    /// Derives the accent colors from `c` so they have enough contrast against `base`
+    /// If a contrast can't be reached, the closest color is used and a warning is logged
+    pub fn derive(
+        c: Lch,
+        base: Lch,
+        is_dark: bool,
+        algorithm: ContrastAlgorithm,
+        space: WorkingSpace,
+    ) -> Self {
+        let (fg_contrast, bg_contrast) = algorithm.accent_contrast(is_dark);
+        let accent_as_fg = derive_or_closest(c, base, fg_contrast, algorithm, space);
+        let accent_bg = derive_or_closest(c, base, bg_contrast, algorithm, space);
+        let accent_fg = text_on(accent_bg, algorithm, space);
+
+        Self {
+            accent: accent_as_fg.into_color(),
+            accent_bg: accent_bg.into_color(),
+            accent_fg: accent_fg.into_color(),
+        }
+    }
+
+    /// Keeps `accent_bg` and derives the accent as text against `base` and the text on the accent
+    pub fn derive_roles(
+        accent_bg: Lch,
+        base: Lch,
+        is_dark: bool,
+        algorithm: ContrastAlgorithm,
+        space: WorkingSpace,
+    ) -> Self {
+        let (fg_contrast, _) = algorithm.accent_contrast(is_dark);
+        let accent_as_fg = derive_or_closest(accent_bg, base, fg_contrast, algorithm, space);
+        let accent_fg = text_on(accent_bg, algorithm, space);
+
+        Self {
+            accent: accent_as_fg.into_color(),
+            accent_bg: accent_bg.into_color(),
+            accent_fg: accent_fg.into_color(),
+        }
+    }
+}
+
+/// Text on `bg`, black or white depending on the polarity with more contrast
+fn text_on(bg: Lch, algorithm: ContrastAlgorithm, space: WorkingSpace) -> Lch {
+    derive_color(bg, bg, None, None, algorithm, space).unwrap()
+}
+
+fn derive_or_closest(
+    c: Lch,
+    base: Lch,
+    contrast: f32,
+    algorithm: ContrastAlgorithm,
+    space: WorkingSpace,
+) -> Lch {
+    derive_color(c, base, Some(contrast), None, algorithm, space).unwrap_or_else(|e| {
+        log::warn!("{}", e);
+        e.closest
+    })
+}
+
+/// The backgrounds accents are drawn on
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Backgrounds {
+    /// `window_bg_color`
+    pub window_bg: Lch,
+    /// `view_bg_color`
+    pub view_bg: Lch,
+}
+
+impl Backgrounds {
+    /// The backgrounds of libadwaita 1.x
+    pub fn adwaita(is_dark: bool) -> Self {
+        let gray = |v: u8| Srgb::new(v, v, v).into_format().into_color();
+        if is_dark {
+            Self {
+                window_bg: gray(0x24),
+                view_bg: gray(0x1e),
+            }
+        } else {
+            Self {
+                window_bg: gray(0xfa),
+                view_bg: gray(0xff),
+            }
+        }
+    }
+
+    /// Replaces the backgrounds that are defined in `css` with `@define-color`
+    pub fn with_css(mut self, css: &str) -> Self {
+        for (name, c) in define_colors(css) {
+            match name.as_str() {
+                "window_bg_color" => self.window_bg = c.into_color(),
+                "view_bg_color" => self.view_bg = c.into_color(),
+                _ => {}
+            }
+        }
+        self
+    }
+
+    /// The background with the least contrast to the accent,
+    /// which is the lightest one for dark & the darkest one for light
+    pub fn worst_case(&self, is_dark: bool) -> Lch {
+        let lighter = self.window_bg.l > self.view_bg.l;
+        if is_dark == lighter {
+            self.window_bg
+        } else {
+            self.view_bg
+        }
+    }
+}
+
+/// A `@define-color` rule for a GTK style sheet
+pub fn define_color(name: &str, c: Srgb) -> String {
+    format!("@define-color {name} #{};", hex_from_srgb(c))
+}
+
+/// Parses the `@define-color name #rrggbb;` rules of a style sheet, rules with other colors are skipped
+pub fn define_colors(css: &str) -> Vec<(String, Srgb)> {
+    css.split(';')
+        .filter_map(|rule| {
+            let rule = &rule[rule.find("@define-color")? + "@define-color".len()..];
+            let mut parts = rule.split_whitespace();
+            let (name, value) = (parts.next()?, parts.next()?);
+            if parts.next().is_some() || !value.starts_with('#') {
+                return None;
+            }
+            Some((name.to_string(), srgb_from_hex(value).ok()?))
+        })
+        .collect()
+}
+
+/// Returned by [`derive_color`] when the requested contrast can't be reached
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct UnreachableContrast {
+    /// the derived color with the highest reachable contrast
+    pub closest: Lch,
+    /// the contrast of `closest`
+    pub contrast: f32,
+    pub target: f32,
+}
+
+impl Display for UnreachableContrast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to derive color with contrast {}, the closest reachable contrast is {}",
+            self.target, self.contrast
+        )
+    }
+}
+
+impl std::error::Error for UnreachableContrast {}
+
+/// The contrast algorithm used when deriving and checking colors
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Default)]
+pub enum ContrastAlgorithm {
+    /// WCAG 2.x contrast ratio, from 1 to 21
+    #[default]
+    Wcag2,
+    /// APCA lightness contrast Lc, from 0 to about 108
+    Apca,
+}
+
+impl ContrastAlgorithm {
+    /// The target contrasts of the accent as text and of the accent background
+    pub fn accent_contrast(self, is_dark: bool) -> (f32, f32) {
+        match (self, is_dark) {
+            (Self::Wcag2, true) => (12.0, 3.0),
+            (Self::Wcag2, false) => (7.0, 1.3), // 7.0 & 1.1 are minimum required
+            // Lc 75 is the minimum for body text & Lc 15 for non-text elements
+            (Self::Apca, true) => (75.0, 30.0),
+            (Self::Apca, false) => (75.0, 15.0),
+        }
+    }
+
+    /// The contrast of `text` drawn on `bg`
+    /// APCA is polarity aware, but only the magnitude of Lc is returned
+    pub fn contrast(self, text: Lch, bg: Lch) -> f32 {
+        match self {
+            Self::Wcag2 => text.get_contrast_ratio(&bg),
+            Self::Apca => apca::lc(apca::luminance(text), apca::luminance(bg)).abs(),
+        }
+    }
+
+    /// The conformance level of text with `contrast`
+    /// WCAG 2 requires 4.5:1 for AA & 7:1 for AAA, APCA has no levels so Lc 60 & Lc 75 are used
+    pub fn text_level(self, contrast: f32) -> ContrastLevel {
+        let (aa, aaa) = match self {
+            Self::Wcag2 => (4.5, 7.0),
+            Self::Apca => (60.0, 75.0),
+        };
+        if contrast >= aaa {
+            ContrastLevel::Aaa
+        } else if contrast >= aa {
+            ContrastLevel::Aa
+        } else {
+            ContrastLevel::Fail
+        }
+    }
+
+    /// The conformance level of non-text elements like backgrounds & borders with `contrast`
+    /// WCAG 2 only has AA with 3:1, which is about Lc 30 in APCA
+    pub fn non_text_level(self, contrast: f32) -> ContrastLevel {
+        let aa = match self {
+            Self::Wcag2 => 3.0,
+            Self::Apca => 30.0,
+        };
+        if contrast >= aa {
+            ContrastLevel::Aa
+        } else {
+            ContrastLevel::Fail
+        }
+    }
+
+    /// Whether an accent background with `contrast` to the window reaches the target of `accent_contrast`
+    /// The target of the light scheme is below `non_text_level`, so this isn't a conformance level
+    pub fn meets_accent_bg(self, contrast: f32, is_dark: bool) -> bool {
+        let (_, target) = self.accent_contrast(is_dark);
+        // the same tolerance as `derive_color`
+        contrast >= target * (1.0 - 1e-3)
+    }
+
+    /// Formats `contrast` like `4.50:1` or `Lc 60.0`
+    pub fn format(self, contrast: f32) -> String {
+        match self {
+            Self::Wcag2 => format!("{contrast:.2}:1"),
+            Self::Apca => format!("Lc {contrast:.1}"),
+        }
+    }
+
+    fn luminance(self, c: Lch) -> f32 {
+        match self {
+            Self::Wcag2 => luminance(c),
+            Self::Apca => apca::luminance(c),
+        }
+    }
+
+    /// Solves the contrast formula for the luminance of text drawn on a background with luminance `bg_y`
+    /// returns the luminance and whether it is reachable
+    fn target_luminance(self, bg_y: f32, target_contrast: f32, lighten: bool) -> (f32, bool) {
+        match self {
+            Self::Wcag2 => target_luminance(bg_y, target_contrast, lighten),
+            Self::Apca => apca::target_luminance(bg_y, target_contrast, lighten),
+        }
+    }
+}
+
+/// The WCAG conformance level passed by a contrast
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum ContrastLevel {
+    Fail,
+    Aa,
+    Aaa,
+}
+
+impl Display for ContrastLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Fail => "Fail",
+            Self::Aa => "AA",
+            Self::Aaa => "AAA",
+        })
+    }
+}
+
+/// The color space in which lightness is changed and palettes are matched
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Default)]
+pub enum WorkingSpace {
+    /// CIE Lch, which shifts blue hues towards purple when changing lightness
+    #[default]
+    CieLch,
+    /// Oklch, which preserves the perceived hue when changing lightness
+    Oklch,
+}
+
+/// the chroma of sRGB colors in Oklch is about 1/400th of their chroma in CIE Lch
+const OKLCH_CHROMA_SCALE: f32 = 400.0;
+
+impl WorkingSpace {
+    /// lightness, chroma & hue in degrees of `c` in this space, scaled to the ranges of CIE Lch
+    pub fn lch_components(self, c: Lch) -> (f32, f32, f32) {
+        match self {
+            Self::CieLch => (c.l, c.chroma, c.hue.to_positive_degrees()),
+            Self::Oklch => {
+                let c: Oklch = c.into_color();
+                (
+                    c.l * 100.0,
+                    c.chroma * OKLCH_CHROMA_SCALE,
+                    c.hue.to_positive_degrees(),
+                )
+            }
+        }
+    }
+
+    /// The color with lightness, chroma & hue in degrees in this space, scaled like in `lch_components`
+    pub fn lch_from_components(self, l: f32, chroma: f32, hue: f32) -> Lch {
+        match self {
+            Self::CieLch => Lch::new(l, chroma, hue),
+            Self::Oklch => {
+                Oklch::new(l / 100.0, chroma / OKLCH_CHROMA_SCALE, hue).into_color_unclamped()
+            }
+        }
+    }
+
+    /// Sets the lightness of `c` in this space, from 0 to 100, keeping hue & chroma
+    fn with_lightness(self, c: Lch, l: f32) -> Lch {
+        match self {
+            Self::CieLch => Lch { l, ..c },
+            Self::Oklch => {
+                let mut c: Oklch = c.into_color_unclamped();
+                c.l = l / 100.0;
+                c.into_color_unclamped()
+            }
+        }
+    }
+
+    /// Sets the chroma of `c` in this space, scaled like in `lch_components`, keeping lightness & hue
+    fn with_chroma(self, c: Lch, chroma: f32) -> Lch {
+        match self {
+            Self::CieLch => Lch { chroma, ..c },
+            Self::Oklch => {
+                let mut c: Oklch = c.into_color_unclamped();
+                c.chroma = chroma / OKLCH_CHROMA_SCALE;
+                c.into_color_unclamped()
+            }
+        }
+    }
+}
+
+/// A component of a color in the working space
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LchChannel {
+    Lightness,
+    Chroma,
+    Hue,
+}
+
+/// a bit above the chroma of the most saturated sRGB colors, in both working spaces
+const SLIDER_MAX_CHROMA: f32 = 135.0;
+
+impl LchChannel {
+    pub const ALL: [Self; 3] = [Self::Lightness, Self::Chroma, Self::Hue];
+
+    /// the range of the component, scaled like in `WorkingSpace::lch_components`
+    pub fn range(self) -> (f32, f32) {
+        match self {
+            Self::Lightness => (0.0, 100.0),
+            Self::Chroma => (0.0, SLIDER_MAX_CHROMA),
+            Self::Hue => (0.0, 360.0),
+        }
+    }
+
+    pub fn get(self, (l, chroma, hue): (f32, f32, f32)) -> f32 {
+        match self {
+            Self::Lightness => l,
+            Self::Chroma => chroma,
+            Self::Hue => hue,
+        }
+    }
+
+    pub fn set(self, (l, chroma, hue): (f32, f32, f32), v: f32) -> (f32, f32, f32) {
+        match self {
+            Self::Lightness => (v, chroma, hue),
+            Self::Chroma => (l, v, hue),
+            Self::Hue => (l, chroma, v),
+        }
+    }
+}
+
+/// The stops of a slider track sweeping `channel` over its range in `space`,
+/// keeping the other components of `c` & mapping each stop into sRGB
+pub fn channel_gradient(
+    c: Lch,
+    channel: LchChannel,
+    space: WorkingSpace,
+    stops: usize,
+) -> Vec<Srgb> {
+    let components = space.lch_components(c);
+    let (min, max) = channel.range();
+    (0..stops)
+        .map(|i| {
+            let v = min + (max - min) * i as f32 / (stops.max(2) - 1) as f32;
+            let (l, chroma, hue) = channel.set(components, v);
+            gamut_map(space.lch_from_components(l, chroma, hue), space).into_color()
+        })
+        .collect()
+}
+
+/// The tones of a tonal scale, as lightness from 0 to 100 in the working space
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum TonalSteps {
+    /// 12 evenly spaced steps from light to dark, without white & black
+    #[default]
+    Twelve,
+    /// tones 100 to 0 in steps of 10
+    Tones,
+}
+
+impl TonalSteps {
+    pub fn tones(self) -> Vec<f32> {
+        match self {
+            Self::Twelve => (1..=12).map(|i| 100.0 - 100.0 * i as f32 / 13.0).collect(),
+            Self::Tones => (0..=10).rev().map(|i| i as f32 * 10.0).collect(),
+        }
+    }
+}
+
+/// above the chroma of any sRGB color in CIE Lch, so gamut mapping finds the maximum chroma
+const MAX_CHROMA: f32 = 150.0;
+/// colors with less chroma in CIE Lch are treated as gray
+const NEUTRAL_CHROMA: f32 = 5.0;
+
+/// A lightness ramp of `c` at constant hue in `space`, each tone with its maximum in-gamut chroma
+/// The ramp of a gray color stays gray
+pub fn tonal_scale(c: Lch, steps: TonalSteps, space: WorkingSpace) -> Vec<Lch> {
+    let chroma = if c.chroma < NEUTRAL_CHROMA {
+        0.0
+    } else {
+        MAX_CHROMA
+    };
+    steps
+        .tones()
+        .into_iter()
+        .map(|tone| {
+            let c = space.with_chroma(space.with_lightness(c, tone), chroma);
+            quantize(gamut_map(c, space))
+        })
+        .collect()
+}
+
+/// just noticeable difference in Oklab, used by the CSS Color 4 gamut mapping
+const JND: f32 = 0.02;
+/// precision of the chroma search, in the chroma scale of CIE Lch
+const GAMUT_EPSILON: f32 = 0.0001 * OKLCH_CHROMA_SCALE;
+
+/// Whether `c` is inside the sRGB gamut, allowing for float noise
+pub fn in_gamut(c: Lch) -> bool {
+    let c: Srgb = c.into_color_unclamped();
+    let tolerance = 1e-4;
+    [c.red, c.green, c.blue]
+        .iter()
+        .all(|v| (-tolerance..=1.0 + tolerance).contains(v))
+}
+
+/// clamps the sRGB channels of `c`
+fn clip(c: Lch) -> Lch {
+    let c: Srgb = c.into_color();
+    c.into_color()
+}
+
+/// rounds `c` to the 8 bit sRGB color that is written out
+fn quantize(c: Lch) -> Lch {
+    let c: Srgb<u8> = Srgb::from_color(c).into_format();
+    c.into_format::<f32>().into_color()
+}
+
+/// euclidean distance in Oklab
+fn delta_eok(c1: Lch, c2: Lch) -> f32 {
+    let c1: Oklab = c1.into_color_unclamped();
+    let c2: Oklab = c2.into_color_unclamped();
+    ((c1.l - c2.l).powi(2) + (c1.a - c2.a).powi(2) + (c1.b - c2.b).powi(2)).sqrt()
+}
+
+/// Maps `c` into sRGB by reducing chroma at constant lightness & hue in `space`
+/// see https://www.w3.org/TR/css-color-4/#binsearch
+pub fn gamut_map(c: Lch, space: WorkingSpace) -> Lch {
+    if in_gamut(c) {
+        return c;
+    }
+
+    let (l, chroma, _) = space.lch_components(c);
+    if l >= 100.0 {
+        return palette::named::WHITE.into_format().into_color();
+    } else if l <= 0.0 {
+        return palette::named::BLACK.into_format().into_color();
+    }
+
+    let mut clipped = clip(c);
+    if delta_eok(clipped, c) < JND {
+        return clipped;
+    }
+
+    let (mut min, mut max) = (0.0, chroma);
+    let mut min_in_gamut = true;
+    while max - min > GAMUT_EPSILON {
+        let chroma = (min + max) / 2.0;
+        let current = space.with_chroma(c, chroma);
+        if min_in_gamut && in_gamut(current) {
+            min = chroma;
+            continue;
+        }
+
+        clipped = clip(current);
+        let e = delta_eok(clipped, current);
+        if e < JND {
+            if JND - e < GAMUT_EPSILON / OKLCH_CHROMA_SCALE {
+                return clipped;
+            }
+            min_in_gamut = false;
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+    clipped
+}
+
+/// Finds the color along the lightness axis of `space` through `c` with luminance `y`
+fn with_luminance(c: Lch, y: f32, algorithm: ContrastAlgorithm, space: WorkingSpace) -> Lch {
+    match (algorithm, space) {
+        (ContrastAlgorithm::Wcag2, WorkingSpace::CieLch) => Lch {
+            l: lightness_from_luminance(y),
+            ..c
+        },
+        // luminance depends on hue & chroma too in these cases, so search along the lightness axis
+        _ => {
+            let (mut l, mut r) = (0.0, 100.0);
+            for _ in 0..32 {
+                let m = (l + r) / 2.0;
+                let current = gamut_map(space.with_lightness(c, m), space);
+                if algorithm.luminance(current) < y {
+                    l = m;
+                } else {
+                    r = m;
+                }
+            }
+            space.with_lightness(c, (l + r) / 2.0)
+        }
+    }
+}
+
+/// the number of steps of `bisect`, which narrows any lightness range to less than 0.01
+const BISECT_STEPS: usize = 16;
+
+/// Searches between `fails` & `passes` for the value closest to `fails` where `ok` holds
+/// `ok` is called `BISECT_STEPS` times
+fn bisect(mut fails: f32, mut passes: f32, mut ok: impl FnMut(f32) -> bool) -> f32 {
+    for _ in 0..BISECT_STEPS {
+        let m = (fails + passes) / 2.0;
+        if ok(m) {
+            passes = m;
+        } else {
+            fails = m;
+        }
+    }
+    passes
+}
+
+/// relative luminance, which is the Y component of CIE XYZ
+fn luminance(c: Lch) -> f32 {
+    Xyz::from_color(c).y
+}
+
+/// Inverts the lightness to luminance conversion of CIE Lab
+/// Luminance only depends on lightness in Lab, so hue and chroma can be ignored
+fn lightness_from_luminance(y: f32) -> f32 {
+    let epsilon = (6.0_f32 / 29.0).powi(3);
+    let kappa = 108.0 / 841.0;
+    let delta = 4.0 / 29.0;
+
+    let t = if y > epsilon {
+        y.cbrt()
+    } else {
+        y / kappa + delta
+    };
+    (116.0 * t - 16.0).clamp(Lch::<D65>::min_l(), Lch::<D65>::max_l())
+}
+
+/// Solves the WCAG contrast ratio for the luminance of the derived color
+/// returns the luminance and whether it is within [0, 1]
+fn target_luminance(base_y: f32, target_contrast: f32, lighten: bool) -> (f32, bool) {
+    let target_contrast = target_contrast.max(1.0);
+    let y = if lighten {
+        target_contrast * (base_y + 0.05) - 0.05
+    } else {
+        (base_y + 0.05) / target_contrast - 0.05
+    };
+    // allow for float noise at the extremes, eg. 21:1 for black on white
+    let tolerance = 1e-5;
+    (
+        y.clamp(0.0, 1.0),
+        (-tolerance..=1.0 + tolerance).contains(&y),
+    )
+}
+
+/// APCA-W3 0.0.98G-4g, see https://github.com/Myndex/apca-w3
+mod apca {
+    use palette::{IntoColor, Lch, Srgb};
+
+    const NORM_BG: f32 = 0.56;
+    const NORM_TXT: f32 = 0.57;
+    const REV_TXT: f32 = 0.62;
+    const REV_BG: f32 = 0.65;
+    const BLK_THRS: f32 = 0.022;
+    const BLK_CLMP: f32 = 1.414;
+    const SCALE: f32 = 1.14;
+    const LO_OFFSET: f32 = 0.027;
+    const LO_CLIP: f32 = 0.1;
+    const DELTA_Y_MIN: f32 = 0.0005;
+
+    /// screen luminance, using the simple 2.4 exponent instead of the piecewise sRGB curve
+    pub fn luminance(c: Lch) -> f32 {
+        let c: Srgb = c.into_color();
+        0.2126729 * c.red.powf(2.4) + 0.7151522 * c.green.powf(2.4) + 0.0721750 * c.blue.powf(2.4)
+    }
+
+    /// soft clamp near black
+    fn clamp_black(y: f32) -> f32 {
+        if y >= BLK_THRS {
+            y
+        } else {
+            y + (BLK_THRS - y).powf(BLK_CLMP)
+        }
+    }
+
+    /// inverse of `clamp_black`, which is monotonic below the threshold
+    fn unclamp_black(y: f32) -> f32 {
+        if y >= BLK_THRS {
+            return y;
+        }
+        let (mut l, mut r) = (0.0, BLK_THRS);
+        for _ in 0..32 {
+            let m = (l + r) / 2.0;
+            if clamp_black(m) < y {
+                l = m;
+            } else {
+                r = m;
+            }
+        }
+        (l + r) / 2.0
+    }
+
+    /// signed Lc, positive for dark text on a light background
+    pub fn lc(text_y: f32, bg_y: f32) -> f32 {
+        let (text_y, bg_y) = (clamp_black(text_y), clamp_black(bg_y));
+        if (bg_y - text_y).abs() < DELTA_Y_MIN {
+            return 0.0;
+        }
+
+        if bg_y > text_y {
+            let s = (bg_y.powf(NORM_BG) - text_y.powf(NORM_TXT)) * SCALE;
+            if s < LO_CLIP {
+                0.0
+            } else {
+                (s - LO_OFFSET) * 100.0
+            }
+        } else {
+            let s = (bg_y.powf(REV_BG) - text_y.powf(REV_TXT)) * SCALE;
+            if s > -LO_CLIP {
+                0.0
+            } else {
+                (s + LO_OFFSET) * 100.0
+            }
+        }
+    }
+
+    /// Solves Lc for the luminance of the text, lighter text uses the reverse polarity
+    pub fn target_luminance(bg_y: f32, target_lc: f32, lighten: bool) -> (f32, bool) {
+        let bg_y = clamp_black(bg_y);
+        let s = (target_lc.abs() / 100.0 + LO_OFFSET) / SCALE;
+        let text_y = if lighten {
+            (bg_y.powf(REV_BG) + s).powf(REV_TXT.recip())
+        } else {
+            let t = bg_y.powf(NORM_BG) - s;
+            if t < 0.0 {
+                return (0.0, false);
+            }
+            t.powf(NORM_TXT.recip())
+        };
+
+        let reachable = (clamp_black(0.0)..=1.0 + 1e-5).contains(&text_y);
+        (unclamp_black(text_y.clamp(0.0, 1.0)), reachable)
+    }
+}
+
+/// Derives a color with the hue & chroma of `lch_color` and the requested contrast against `base`
+/// Hue & chroma are kept in `space`
+/// `lighten` restricts the direction, otherwise the direction with more headroom is preferred
+/// The derived color is treated as text drawn on `base` by polarity aware algorithms
+pub fn derive_color(
+    lch_color: Lch,
+    base: Lch,
+    contrast: Option<f32>,
+    lighten: Option<bool>,
+    algorithm: ContrastAlgorithm,
+    space: WorkingSpace,
+) -> Result<Lch, UnreachableContrast> {
+    if let Some(target_contrast) = contrast {
+        let base_y = algorithm.luminance(base);
+
+        let derive = |lighten: bool| {
+            let (y, reachable) = algorithm.target_luminance(base_y, target_contrast, lighten);
+            let mut derived = with_luminance(lch_color, y, algorithm, space);
+            // clamp to valid value in range
+            derived.clamp_self();
+            let mapped = gamut_map(derived, space);
+
+            // verify contrast & direction after mapping & rounding to 8 bit channels,
+            // and search for the closest lightness that meets both again
+            let white: Lch = palette::named::WHITE.into_format().into_color();
+            let black: Lch = palette::named::BLACK.into_format().into_color();
+            // the ends are set directly, as the round trip through `space` can miss them slightly
+            let at = |l: f32| match l {
+                l if l >= 100.0 => white,
+                l if l <= 0.0 => black,
+                l => quantize(gamut_map(space.with_lightness(mapped, l), space)),
+            };
+            let meets_target = |c: Lch| {
+                let y = algorithm.luminance(c);
+                let right_side = if lighten { y >= base_y } else { y <= base_y };
+                right_side && algorithm.contrast(c, base) >= target_contrast
+            };
+            let mut derived = quantize(mapped);
+            if !meets_target(derived) {
+                let (l, _, _) = space.lch_components(mapped);
+                let end = if lighten { 100.0 } else { 0.0 };
+                derived = at(end);
+                if meets_target(derived) {
+                    derived = at(bisect(l, end, |l| meets_target(at(l))));
+                }
+            }
+
+            // the luminance may not be reachable at this hue & chroma
+            let actual_contrast = algorithm.contrast(derived, base);
+            (
+                derived,
+                reachable && actual_contrast >= target_contrast * (1.0 - 1e-3),
+            )
+        };
+
+        let (derived, reachable) = match lighten {
+            Some(lighten) => derive(lighten),
+            None => {
+                // prefer the direction with more headroom
+                let (preferred, other) = if base.l < 50.0 {
+                    (derive(true), derive(false))
+                } else {
+                    (derive(false), derive(true))
+                };
+                match (preferred, other) {
+                    (preferred, _) if preferred.1 => preferred,
+                    (_, other) if other.1 => other,
+                    (preferred, other) => {
+                        if algorithm.contrast(preferred.0, base)
+                            >= algorithm.contrast(other.0, base)
+                        {
+                            preferred
+                        } else {
+                            other
+                        }
+                    }
+                }
+            }
+        };
+
+        if reachable {
+            Ok(derived)
+        } else {
+            Err(UnreachableContrast {
+                closest: derived,
+                contrast: algorithm.contrast(derived, base),
+                target: target_contrast,
+            })
+        }
+    } else {
+        // maximize contrast if no constraint is given
+        let black: Lch = palette::named::BLACK.into_format().into_color();
+        let white: Lch = palette::named::WHITE.into_format().into_color();
+        if algorithm.contrast(black, base) > algorithm.contrast(white, base) {
+            Ok(black)
+        } else {
+            Ok(white)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const ALGORITHMS: [ContrastAlgorithm; 2] = [ContrastAlgorithm::Wcag2, ContrastAlgorithm::Apca];
+    const SPACES: [WorkingSpace; 2] = [WorkingSpace::CieLch, WorkingSpace::Oklch];
+
+    /// any Lch color, including ones outside of sRGB
+    fn lch() -> impl Strategy<Value = Lch> {
+        (0.0f32..=100.0, 0.0f32..=130.0, 0.0f32..360.0).prop_map(|(l, c, h)| Lch::new(l, c, h))
+    }
+
+    /// backgrounds are always in sRGB
+    fn srgb_lch() -> impl Strategy<Value = Lch> {
+        (0.0f32..=1.0, 0.0f32..=1.0, 0.0f32..=1.0)
+            .prop_map(|(r, g, b)| Srgb::new(r, g, b).into_color())
+    }
+
+    fn algorithm() -> impl Strategy<Value = ContrastAlgorithm> {
+        prop::sample::select(ALGORITHMS.to_vec())
+    }
+
+    fn space() -> impl Strategy<Value = WorkingSpace> {
+        prop::sample::select(SPACES.to_vec())
+    }
+
+    /// a contrast target within the range of `algorithm`, as a fraction of the range
+    fn target(algorithm: ContrastAlgorithm, t: f32) -> f32 {
+        match algorithm {
+            ContrastAlgorithm::Wcag2 => 1.0 + t * 20.0,
+            ContrastAlgorithm::Apca => t * 105.0,
+        }
+    }
+
+    /// about the contrast of one lightness step when nudging after rounding
+    fn tolerance(algorithm: ContrastAlgorithm) -> f32 {
+        match algorithm {
+            ContrastAlgorithm::Wcag2 => 0.05,
+            ContrastAlgorithm::Apca => 0.5,
+        }
+    }
+
+    fn derived(res: Result<Lch, UnreachableContrast>) -> Lch {
+        res.unwrap_or_else(|e| e.closest)
+    }
+
+    proptest! {
+        #[test]
+        fn derive_meets_contrast_or_reports_failure(
+            c in lch(),
+            base in srgb_lch(),
+            t in 0.0f32..=1.0,
+            lighten in prop::option::of(any::<bool>()),
+            algorithm in algorithm(),
+            space in space(),
+        ) {
+            let target = target(algorithm, t);
+            match derive_color(c, base, Some(target), lighten, algorithm, space) {
+                Ok(derived) => {
+                    let contrast = algorithm.contrast(derived, base);
+                    prop_assert!(contrast >= target * (1.0 - 1e-3), "{} < {}", contrast, target);
+                }
+                Err(e) => {
+                    prop_assert_eq!(e.target, target);
+                    let contrast = algorithm.contrast(e.closest, base);
+                    prop_assert!((e.contrast - contrast).abs() < 1e-4);
+                    prop_assert!(contrast < target * (1.0 + 1e-3), "{} >= {}", contrast, target);
+                }
+            }
+        }
+
+        #[test]
+        fn derive_respects_direction(
+            c in lch(),
+            base in srgb_lch(),
+            t in 0.0f32..=1.0,
+            lighten in any::<bool>(),
+            algorithm in algorithm(),
+            space in space(),
+        ) {
+            let target = target(algorithm, t);
+            let res = derive_color(c, base, Some(target), Some(lighten), algorithm, space);
+            let (y, base_y) = (algorithm.luminance(derived(res)), algorithm.luminance(base));
+            if lighten {
+                prop_assert!(y >= base_y, "{} darker than {}", y, base_y);
+            } else {
+                prop_assert!(y <= base_y, "{} lighter than {}", y, base_y);
+            }
+        }
+
+        #[test]
+        fn derive_is_monotonic_in_target(
+            c in lch(),
+            base in srgb_lch(),
+            t1 in 0.0f32..=1.0,
+            t2 in 0.0f32..=1.0,
+            lighten in any::<bool>(),
+            algorithm in algorithm(),
+            space in space(),
+        ) {
+            let (t1, t2) = (t1.min(t2), t1.max(t2));
+            let contrast = |t| {
+                let target = Some(target(algorithm, t));
+                let res = derive_color(c, base, target, Some(lighten), algorithm, space);
+                algorithm.contrast(derived(res), base)
+            };
+            // a higher target moves further away from the background
+            let (contrast1, contrast2) = (contrast(t1), contrast(t2));
+            prop_assert!(
+                contrast2 >= contrast1 - tolerance(algorithm),
+                "{} < {}",
+                contrast2,
+                contrast1
+            );
+        }
+
+        #[test]
+        fn derive_stays_in_gamut(
+            c in lch(),
+            base in srgb_lch(),
+            t in prop::option::of(0.0f32..=1.0),
+            lighten in prop::option::of(any::<bool>()),
+            algorithm in algorithm(),
+            space in space(),
+        ) {
+            let target = t.map(|t| target(algorithm, t));
+            let derived = derived(derive_color(c, base, target, lighten, algorithm, space));
+            prop_assert!(in_gamut(derived), "{:?}", derived);
+        }
+
+        #[test]
+        fn hex_round_trips(r: u8, g: u8, b: u8) {
+            let c = Srgb::new(r, g, b).into_format();
+            let hex = hex_from_srgb(c);
+            prop_assert_eq!(hex.len(), 6);
+            prop_assert_eq!(srgb_from_hex(&hex).unwrap(), c);
+            prop_assert_eq!(srgb_from_hex(&format!("#{}", hex.to_uppercase())).unwrap(), c);
+        }
+    }
+
+    #[test]
+    fn contrast_levels() {
+        let wcag2 = ContrastAlgorithm::Wcag2;
+        assert_eq!(wcag2.text_level(4.49), ContrastLevel::Fail);
+        assert_eq!(wcag2.text_level(4.5), ContrastLevel::Aa);
+        assert_eq!(wcag2.text_level(21.0), ContrastLevel::Aaa);
+        assert_eq!(wcag2.non_text_level(2.99), ContrastLevel::Fail);
+        assert_eq!(wcag2.non_text_level(3.0), ContrastLevel::Aa);
+        assert_eq!(wcag2.non_text_level(21.0), ContrastLevel::Aa);
+        assert!(wcag2.meets_accent_bg(1.3, false) && !wcag2.meets_accent_bg(1.3, true));
+
+        let black: Lch = Srgb::new(0.0, 0.0, 0.0).into_color();
+        let white: Lch = Srgb::new(1.0, 1.0, 1.0).into_color();
+        for algorithm in ALGORITHMS {
+            assert_eq!(
+                algorithm.text_level(algorithm.contrast(black, white)),
+                ContrastLevel::Aaa
+            );
+            assert_eq!(
+                algorithm.non_text_level(algorithm.contrast(white, white)),
+                ContrastLevel::Fail
+            );
+            assert!(!algorithm.meets_accent_bg(algorithm.contrast(white, white), false));
+
+            // derived accent backgrounds reach the target shown by the contrast report
+            for is_dark in [false, true] {
+                let backgrounds = Backgrounds::adwaita(is_dark);
+                let base = backgrounds.worst_case(is_dark);
+                for hex in ["3584e4", "f6d32d", "26a269"] {
+                    let c = srgb_from_hex(hex).unwrap().into_color();
+                    let accent = Accent::derive(c, base, is_dark, algorithm, WorkingSpace::CieLch);
+                    for bg in [backgrounds.window_bg, backgrounds.view_bg] {
+                        let contrast = algorithm.contrast(accent.accent_bg.into_color(), bg);
+                        assert!(
+                            algorithm.meets_accent_bg(contrast, is_dark),
+                            "{hex} {contrast}"
+                        );
+                    }
+                }
+            }
+        }
+        assert_eq!(wcag2.format(4.5), "4.50:1");
+        assert_eq!(ContrastAlgorithm::Apca.format(60.04), "Lc 60.0");
+    }
+
+    #[test]
+    fn accent_css_round_trips() {
+        let accent = Accent::derive(
+            srgb_from_hex("3584e4").unwrap().into_color(),
+            Backgrounds::adwaita(false).worst_case(false),
+            false,
+            ContrastAlgorithm::Wcag2,
+            WorkingSpace::CieLch,
+        );
+        let parsed = define_colors(&accent.to_css());
+        let roles = accent
+            .roles()
+            .map(|(name, c)| (name.to_string(), hex_from_srgb(c)));
+        let parsed: Vec<_> = parsed
+            .into_iter()
+            .map(|(name, c)| (name, hex_from_srgb(c)))
+            .collect();
+        assert_eq!(parsed, roles);
+    }
+
+    #[test]
+    fn backgrounds_from_css() {
+        let css = "/* colors */\n@define-color window_bg_color #303030;\n\
+                   @define-color view_bg_color @window_bg_color;\n\
+                   @define-color headerbar_bg_color #303030;";
+        assert_eq!(define_colors(css).len(), 2);
+
+        let backgrounds = Backgrounds::adwaita(true).with_css(css);
+        assert_eq!(hex_from_srgb(backgrounds.window_bg.into_color()), "303030");
+        assert_eq!(backgrounds.view_bg, Backgrounds::adwaita(true).view_bg);
+        // the lighter background has the least contrast to a light accent
+        assert_eq!(backgrounds.worst_case(true), backgrounds.window_bg);
+        assert_eq!(backgrounds.worst_case(false), backgrounds.view_bg);
+    }
+
+    #[test]
+    fn channel_gradients_stay_in_gamut() {
+        let c: Lch = srgb_from_hex("3584e4").unwrap().into_color();
+        for space in SPACES {
+            let components = space.lch_components(c);
+            for channel in LchChannel::ALL {
+                let stops = channel_gradient(c, channel, space, 13);
+                assert_eq!(stops.len(), 13);
+                assert!(stops.iter().all(|s| in_gamut((*s).into_color())));
+            }
+            let lightness = channel_gradient(c, LchChannel::Lightness, space, 13);
+            assert_eq!(hex_from_srgb(lightness[0]), "000000");
+            assert_eq!(hex_from_srgb(lightness[12]), "ffffff");
+
+            let (l, chroma, hue) = components;
+            let round_trip = space.lch_components(space.lch_from_components(l, chroma, hue));
+            assert!((round_trip.0 - l).abs() < 1e-3 && (round_trip.2 - hue).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn derive_search_is_bounded() {
+        let mut calls = 0;
+        let found = bisect(0.0, 100.0, |l| {
+            calls += 1;
+            l >= 42.0
+        });
+        assert_eq!(calls, BISECT_STEPS);
+        assert!((42.0..42.01).contains(&found));
+
+        // saturated colors at the edge of the gamut need the most correction after mapping
+        let dark_base = Backgrounds::adwaita(true).worst_case(true);
+        for hex in ["0000ff", "ff00ff", "00ff00", "ff0000"] {
+            let c: Lch = srgb_from_hex(hex).unwrap().into_color();
+            for space in SPACES {
+                let derived = derive_color(
+                    c,
+                    dark_base,
+                    Some(75.0),
+                    Some(true),
+                    ContrastAlgorithm::Apca,
+                    space,
+                )
+                .unwrap();
+                assert!(ContrastAlgorithm::Apca.contrast(derived, dark_base) >= 75.0);
+            }
+        }
+    }
+
+    #[test]
+    fn hex_rejects_invalid_colors() {
+        assert_eq!(srgb_from_hex("#fff").unwrap(), Srgb::new(1.0, 1.0, 1.0));
+        for s in ["", "#ff", "#fffff", "#ggg", "#3584e4ff"] {
+            assert!(srgb_from_hex(s).is_err(), "{:?}", s);
+        }
+    }
+}
@@ -0,0 +1,582 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+//! Parses & writes colors in the notations of CSS Color 4
+//!
+//! Accents are opaque, so alpha is checked but dropped. Colors outside of sRGB are gamut mapped.
+
+use palette::{
+    chromatic_adaptation::AdaptInto, white_point::D50, FromColor, Hsl, Hwb, IntoColor, Lab, Lch,
+    Oklab, Oklch, Srgb,
+};
+use std::fmt::{self, Display};
+
+use crate::color::{self, WorkingSpace};
+
+/// Returned when a color can't be parsed
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    /// the position of the offending character, counted in chars
+    pub position: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Ident(String),
+    /// an identifier directly followed by `(`
+    Function(String),
+    /// the digits after `#`
+    Hash(String),
+    Number(f32),
+    Percentage(f32),
+    Dimension(f32, String),
+    Comma,
+    Slash,
+    CloseParen,
+}
+
+/// Splits `chars` into tokens with their positions, skipping whitespace
+fn tokenize(chars: &[char]) -> Result<Vec<(usize, Token)>, ParseError> {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    let take_while = |mut i: usize, f: &dyn Fn(char) -> bool| {
+        while i < chars.len() && f(chars[i]) {
+            i += 1;
+        }
+        i
+    };
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        let starts_number = |i: usize| {
+            let digit_at = |i: usize| chars.get(i).is_some_and(char::is_ascii_digit);
+            digit_at(i) || (chars.get(i) == Some(&'.') && digit_at(i + 1))
+        };
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        } else if c == '#' {
+            i = take_while(i + 1, &is_ident);
+            tokens.push((start, Token::Hash(chars[start + 1..i].iter().collect())));
+        } else if starts_number(i) || (matches!(c, '+' | '-') && starts_number(i + 1)) {
+            i = take_while(i + 1, &|c| c.is_ascii_digit());
+            if chars.get(i) == Some(&'.') {
+                i = take_while(i + 1, &|c| c.is_ascii_digit());
+            }
+            // only an exponent if digits follow, `1e` is a dimension
+            if matches!(chars.get(i), Some('e' | 'E')) {
+                let digits = if matches!(chars.get(i + 1), Some('+' | '-')) {
+                    i + 2
+                } else {
+                    i + 1
+                };
+                if chars.get(digits).is_some_and(char::is_ascii_digit) {
+                    i = take_while(digits, &|c| c.is_ascii_digit());
+                }
+            }
+            let number: String = chars[start..i].iter().collect();
+            let number = number
+                .parse::<f32>()
+                .map_err(|_| ParseError::new(start, "Invalid number"))?;
+            if chars.get(i) == Some(&'%') {
+                i += 1;
+                tokens.push((start, Token::Percentage(number)));
+            } else if chars.get(i).is_some_and(|c| c.is_ascii_alphabetic()) {
+                let unit_start = i;
+                i = take_while(i, &is_ident);
+                let unit = chars[unit_start..i].iter().collect();
+                tokens.push((start, Token::Dimension(number, unit)));
+            } else {
+                tokens.push((start, Token::Number(number)));
+            }
+        } else if c.is_ascii_alphabetic() || c == '-' || c == '_' {
+            i = take_while(i, &is_ident);
+            let name = chars[start..i].iter().collect::<String>().to_lowercase();
+            if chars.get(i) == Some(&'(') {
+                i += 1;
+                tokens.push((start, Token::Function(name)));
+            } else {
+                tokens.push((start, Token::Ident(name)));
+            }
+        } else {
+            let token = match c {
+                ',' => Token::Comma,
+                '/' => Token::Slash,
+                ')' => Token::CloseParen,
+                _ => return Err(ParseError::new(start, format!("Unexpected `{c}`"))),
+            };
+            i += 1;
+            tokens.push((start, token));
+        }
+    }
+    Ok(tokens)
+}
+
+/// A component of a color function
+#[derive(Clone, PartialEq, Debug)]
+enum Value {
+    Number(f32),
+    Percentage(f32),
+    Dimension(f32, String),
+    /// a missing component, which is treated as 0
+    None,
+}
+
+impl Value {
+    /// the value of a number or percentage, where 100% is `percent_scale`
+    fn number(&self, position: usize, percent_scale: f32) -> Result<f32, ParseError> {
+        match self {
+            Self::Number(n) => Ok(*n),
+            Self::Percentage(p) => Ok(p / 100.0 * percent_scale),
+            Self::Dimension(_, unit) => Err(ParseError::new(
+                position,
+                format!("Unexpected unit `{unit}`, expected a number or percentage"),
+            )),
+            Self::None => Ok(0.0),
+        }
+    }
+
+    /// a hue in degrees, from a number or an angle
+    fn hue(&self, position: usize) -> Result<f32, ParseError> {
+        let degrees = match self {
+            Self::Number(n) => *n,
+            Self::Dimension(n, unit) => match unit.to_lowercase().as_str() {
+                "deg" => *n,
+                "rad" => n.to_degrees(),
+                "grad" => n * 0.9,
+                "turn" => n * 360.0,
+                _ => {
+                    return Err(ParseError::new(
+                        position,
+                        format!("Unexpected unit `{unit}`, expected an angle"),
+                    ))
+                }
+            },
+            Self::Percentage(_) => {
+                return Err(ParseError::new(
+                    position,
+                    "Expected a hue, not a percentage",
+                ))
+            }
+            Self::None => 0.0,
+        };
+        Ok(degrees.rem_euclid(360.0))
+    }
+}
+
+/// The arguments of a color function, with the position of each component
+struct Arguments {
+    components: Vec<(usize, Value)>,
+    alpha: Option<(usize, Value)>,
+}
+
+/// Parses the arguments after `name(` up to the closing parenthesis,
+/// in the modern syntax `a b c / alpha` or the legacy syntax `a, b, c, alpha`
+fn arguments(
+    name: &str,
+    tokens: &mut std::iter::Peekable<std::vec::IntoIter<(usize, Token)>>,
+    end: usize,
+) -> Result<Arguments, ParseError> {
+    let allows_commas = matches!(name, "rgb" | "rgba" | "hsl" | "hsla");
+    let value = |tokens: &mut std::iter::Peekable<_>| match tokens.next() {
+        Some((i, Token::Number(n))) => Ok((i, Value::Number(n))),
+        Some((i, Token::Percentage(p))) => Ok((i, Value::Percentage(p))),
+        Some((i, Token::Dimension(n, unit))) => Ok((i, Value::Dimension(n, unit))),
+        Some((i, Token::Ident(ident))) if ident == "none" => Ok((i, Value::None)),
+        Some((i, _)) => Err(ParseError::new(i, "Expected a number")),
+        None => Err(ParseError::new(end, "Expected a number")),
+    };
+
+    let mut components = vec![value(tokens)?];
+    let mut alpha = None;
+    let mut legacy = None;
+    let close = loop {
+        match tokens.peek().cloned() {
+            Some((i, Token::CloseParen)) => {
+                tokens.next();
+                break i;
+            }
+            Some((i, Token::Comma)) => {
+                if !allows_commas {
+                    return Err(ParseError::new(
+                        i,
+                        format!("`{name}()` separates components with spaces, not commas"),
+                    ));
+                } else if legacy == Some(false) {
+                    return Err(ParseError::new(i, "Unexpected `,` between spaces"));
+                }
+                legacy = Some(true);
+                tokens.next();
+                components.push(value(tokens)?);
+            }
+            Some((i, Token::Slash)) => {
+                if legacy == Some(true) {
+                    return Err(ParseError::new(i, "Unexpected `/` between commas"));
+                }
+                tokens.next();
+                alpha = Some(value(tokens)?);
+                match tokens.next() {
+                    Some((i, Token::CloseParen)) => break i,
+                    Some((i, _)) => return Err(ParseError::new(i, "Expected `)`")),
+                    None => return Err(ParseError::new(end, "Expected `)`")),
+                }
+            }
+            Some((i, _)) => {
+                if legacy == Some(true) {
+                    return Err(ParseError::new(i, "Expected `,`"));
+                }
+                legacy = Some(false);
+                components.push(value(tokens)?);
+            }
+            None => return Err(ParseError::new(end, "Expected `)`")),
+        }
+    };
+
+    if legacy == Some(true) && components.len() == 4 {
+        alpha = components.pop();
+    }
+    if components.len() != 3 {
+        return Err(ParseError::new(
+            close,
+            format!("`{name}()` takes 3 components, found {}", components.len()),
+        ));
+    }
+    Ok(Arguments { components, alpha })
+}
+
+/// Parses `#rgb`, `#rgba`, `#rrggbb` & `#rrggbbaa`, `position` is the one of `#`
+fn hex(digits: &str, position: usize) -> Result<Srgb, ParseError> {
+    if let Some(i) = digits.chars().position(|c| !c.is_ascii_hexdigit()) {
+        return Err(ParseError::new(position + 1 + i, "Invalid hex digit"));
+    }
+    let channel = |i: usize, len: usize| {
+        let v = u8::from_str_radix(&digits[i * len..(i + 1) * len], 16).unwrap();
+        if len == 1 {
+            v * 17
+        } else {
+            v
+        }
+    };
+    let len = match digits.len() {
+        3 | 4 => 1,
+        6 | 8 => 2,
+        _ => {
+            return Err(ParseError::new(
+                position,
+                "Expected 3, 4, 6 or 8 hex digits",
+            ))
+        }
+    };
+    Ok(Srgb::new(channel(0, len), channel(1, len), channel(2, len)).into_format())
+}
+
+/// Converts the arguments of a color function to sRGB
+fn function(name: &str, args: &Arguments) -> Result<Srgb, ParseError> {
+    let [(p0, v0), (p1, v1), (p2, v2)] = match args.components.as_slice() {
+        [a, b, c] => [a, b, c],
+        _ => unreachable!(),
+    };
+    let (p0, p1, p2) = (*p0, *p1, *p2);
+    if let Some((i, alpha)) = &args.alpha {
+        alpha.number(*i, 1.0)?;
+    }
+
+    let lch: Lch = match name {
+        "rgb" | "rgba" => {
+            let channel = |v: &Value, i| Ok((v.number(i, 255.0)? / 255.0).clamp(0.0, 1.0));
+            return Ok(Srgb::new(
+                channel(v0, p0)?,
+                channel(v1, p1)?,
+                channel(v2, p2)?,
+            ));
+        }
+        "hsl" | "hsla" => {
+            let c = Hsl::new(
+                v0.hue(p0)?,
+                (v1.number(p1, 100.0)? / 100.0).clamp(0.0, 1.0),
+                (v2.number(p2, 100.0)? / 100.0).clamp(0.0, 1.0),
+            );
+            return Ok(Srgb::from_color(c));
+        }
+        "hwb" => {
+            let mut w = (v1.number(p1, 100.0)? / 100.0).clamp(0.0, 1.0);
+            let mut b = (v2.number(p2, 100.0)? / 100.0).clamp(0.0, 1.0);
+            // whiteness & blackness that add up to more than 100% are a gray
+            if w + b > 1.0 {
+                let sum = w + b;
+                w /= sum;
+                b /= sum;
+            }
+            return Ok(Srgb::from_color(Hwb::new(v0.hue(p0)?, w, b)));
+        }
+        "lab" => Lab::<D50>::with_wp(
+            v0.number(p0, 100.0)?.max(0.0),
+            v1.number(p1, 125.0)?,
+            v2.number(p2, 125.0)?,
+        )
+        .adapt_into(),
+        "lch" => Lch::<D50>::with_wp(
+            v0.number(p0, 100.0)?.max(0.0),
+            v1.number(p1, 150.0)?.max(0.0),
+            v2.hue(p2)?,
+        )
+        .adapt_into(),
+        "oklab" => Oklab::new(
+            v0.number(p0, 1.0)?.max(0.0),
+            v1.number(p1, 0.4)?,
+            v2.number(p2, 0.4)?,
+        )
+        .into_color(),
+        "oklch" => Oklch::new(
+            v0.number(p0, 1.0)?.max(0.0),
+            v1.number(p1, 0.4)?.max(0.0),
+            v2.hue(p2)?,
+        )
+        .into_color(),
+        _ => unreachable!("the name is checked before parsing the arguments"),
+    };
+    Ok(color::gamut_map(lch, WorkingSpace::Oklch).into_color())
+}
+
+/// Parses a CSS color: `#rgb`, `#rrggbb(aa)`, `rgb()`, `hsl()`, `hwb()`, `lab()`, `lch()`,
+/// `oklab()`, `oklch()` or a named color
+pub fn parse(s: &str) -> Result<Srgb, ParseError> {
+    let chars: Vec<char> = s.chars().collect();
+    let end = chars.len();
+    let mut tokens = tokenize(&chars)?.into_iter().peekable();
+
+    let c = match tokens.next() {
+        Some((i, Token::Hash(digits))) => hex(&digits, i)?,
+        Some((i, Token::Ident(name))) => palette::named::from_str(&name)
+            .ok_or_else(|| ParseError::new(i, format!("Unknown color name `{name}`")))?
+            .into_format(),
+        Some((i, Token::Function(name))) => {
+            // check the name before the arguments, for the more helpful error
+            if !matches!(
+                name.as_str(),
+                "rgb" | "rgba" | "hsl" | "hsla" | "hwb" | "lab" | "lch" | "oklab" | "oklch"
+            ) {
+                return Err(ParseError::new(
+                    i,
+                    format!("Unknown color function `{name}()`"),
+                ));
+            }
+            let args = arguments(&name, &mut tokens, end)?;
+            function(&name, &args)?
+        }
+        Some((i, _)) => return Err(ParseError::new(i, "Expected a color")),
+        None => return Err(ParseError::new(end, "Expected a color")),
+    };
+
+    match tokens.next() {
+        Some((i, _)) => Err(ParseError::new(i, "Unexpected input after the color")),
+        None => Ok(c),
+    }
+}
+
+/// The notations colors are written in
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Notation {
+    Hex,
+    Rgb,
+    Hsl,
+    Lab,
+    Lch,
+    Oklch,
+}
+
+impl Notation {
+    pub const ALL: [Self; 6] = [
+        Self::Hex,
+        Self::Rgb,
+        Self::Hsl,
+        Self::Lab,
+        Self::Lch,
+        Self::Oklch,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hex => "Hex",
+            Self::Rgb => "RGB",
+            Self::Hsl => "HSL",
+            Self::Lab => "Lab",
+            Self::Lch => "Lch",
+            Self::Oklch => "Oklch",
+        }
+    }
+}
+
+/// `v` with up to `decimals` decimals, without trailing zeros
+fn number(v: f32, decimals: usize) -> String {
+    let s = format!("{v:.decimals$}");
+    let s = if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &s
+    };
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// Writes `c` in `notation`, precise enough to parse back to the same 8 bit color
+pub fn to_css(c: Srgb, notation: Notation) -> String {
+    match notation {
+        Notation::Hex => format!("#{}", color::hex_from_srgb(c)),
+        Notation::Rgb => {
+            let c: Srgb<u8> = c.into_format();
+            format!("rgb({} {} {})", c.red, c.green, c.blue)
+        }
+        Notation::Hsl => {
+            let c = Hsl::from_color(c);
+            format!(
+                "hsl({} {}% {}%)",
+                number(c.hue.to_positive_degrees(), 2),
+                number(c.saturation * 100.0, 2),
+                number(c.lightness * 100.0, 2)
+            )
+        }
+        Notation::Lab => {
+            let c: Lab<D50> = c.adapt_into();
+            format!(
+                "lab({} {} {})",
+                number(c.l, 2),
+                number(c.a, 2),
+                number(c.b, 2)
+            )
+        }
+        Notation::Lch => {
+            let c: Lch<D50> = c.adapt_into();
+            format!(
+                "lch({} {} {})",
+                number(c.l, 2),
+                number(c.chroma, 2),
+                number(c.hue.to_positive_degrees(), 2)
+            )
+        }
+        Notation::Oklch => {
+            let c: Oklch = c.into_color();
+            format!(
+                "oklch({}% {} {})",
+                number(c.l * 100.0, 3),
+                number(c.chroma, 5),
+                number(c.hue.to_positive_degrees(), 2)
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn hex_of(s: &str) -> String {
+        color::hex_from_srgb(parse(s).unwrap())
+    }
+
+    #[test]
+    fn parses_every_notation() {
+        for s in [
+            "#36c",
+            "#36cf",
+            "#3366cc",
+            "#3366CC80",
+            "rgb(51 102 204)",
+            "rgb(51, 102, 204)",
+            "rgba(51, 102, 204, 0.5)",
+            "rgb(20% 40% 80% / 50%)",
+            "hsl(220 60% 50%)",
+            "hsla(220deg, 60%, 50%, 1)",
+            "hsl(0.6111turn 60% 50%)",
+            "hwb(220 20% 20%)",
+            "lab(44.12 10.96 -59.09)",
+            "lch(44.12 60.09 280.51)",
+            "oklab(0.5325 -0.0225 -0.1664)",
+            "oklch(53.25% 0.168 262.3)",
+        ] {
+            assert_eq!(hex_of(s), "3366cc", "{s}");
+        }
+        assert_eq!(hex_of("RebeccaPurple"), "663399");
+        assert_eq!(hex_of("  white "), "ffffff");
+        assert_eq!(hex_of("rgb(none 300 -5)"), "00ff00");
+        assert_eq!(hex_of("hwb(0 80% 80%)"), "808080");
+        // outside of sRGB
+        assert!(parse("oklch(70% 0.4 150)").is_ok());
+    }
+
+    proptest! {
+        #[test]
+        fn written_colors_parse_back(r: u8, g: u8, b: u8) {
+            let c: Srgb = Srgb::new(r, g, b).into_format();
+            for notation in Notation::ALL {
+                let css = to_css(c, notation);
+                prop_assert_eq!(hex_of(&css), color::hex_from_srgb(c), "{}", css);
+            }
+        }
+    }
+
+    #[test]
+    fn writes_every_notation() {
+        let c = color::srgb_from_hex("3366cc").unwrap();
+        let written: Vec<_> = Notation::ALL.iter().map(|n| to_css(c, *n)).collect();
+        assert_eq!(
+            written,
+            [
+                "#3366cc",
+                "rgb(51 102 204)",
+                "hsl(220 60% 50%)",
+                "lab(44.12 10.96 -59.09)",
+                "lch(44.12 60.09 280.51)",
+                "oklch(53.247% 0.16793 262.3)",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        let error = |s: &str| {
+            let err = parse(s).unwrap_err();
+            (err.position, err.message)
+        };
+        assert_eq!(error("").0, 0);
+        assert_eq!(error("#12345").0, 0);
+        assert_eq!(error("#12g").0, 3);
+        assert_eq!(error("blurple").0, 0);
+        assert_eq!(error("rgb(1 2)").0, 7);
+        assert_eq!(error("rgb(1, 2 3)").0, 9);
+        assert_eq!(error("lab(1, 2, 3)").0, 5);
+        assert_eq!(error("hsl(10% 50% 50%)").0, 4);
+        assert_eq!(error("hsl(10px 50% 50%)").0, 4);
+        assert_eq!(error("oklch(0.5 0.1 10").0, 16);
+        assert_eq!(error("cmyk(0 0 0 0)").0, 0);
+        assert_eq!(error("red blue").0, 4);
+        assert_eq!(error("rgb(1 2 3 / 4 5)").0, 14);
+        assert_eq!(error("rgb(1 2 3 ; 4)").0, 10);
+        // positions count chars, not bytes
+        assert_eq!(error("rgb(1 2 3 ✓)").0, 10);
+    }
+}
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use kmeans_colors::{get_kmeans_hamerly, Kmeans, Sort};
+use palette::{IntoColor, Lab, Lch, Srgb};
+use std::collections::HashSet;
+
+use crate::color::{self, WorkingSpace};
+
+/// number of clusters & k-means runs, the run with the best score is used
+const EXTRACT_CLUSTERS: usize = 8;
+const EXTRACT_RUNS: u64 = 3;
+/// clusters with less chroma in CIE Lch are too neutral for an accent
+const MIN_ACCENT_CHROMA: f32 = 15.0;
+/// clusters covering less of the image are ignored
+const MIN_COVERAGE: f32 = 0.02;
+
+/// A color extracted from an image
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct AccentCandidate {
+    pub color: Lch,
+    /// the share of the image covered by the color, from 0 to 1
+    pub coverage: f32,
+}
+
+impl AccentCandidate {
+    /// colorful colors covering a large part of the image make the best accents
+    pub fn score(&self) -> f32 {
+        self.color.chroma * self.coverage.sqrt()
+    }
+}
+
+/// Clusters `pixels` and ranks the clusters that aren't neutral or tiny by chroma & coverage
+pub fn accent_candidates(pixels: &[Lab], count: usize) -> Vec<AccentCandidate> {
+    if pixels.is_empty() {
+        return vec![];
+    }
+
+    // k-means++ needs at least as many distinct colors as clusters, e.g. for solid color wallpapers
+    let distinct: HashSet<[u32; 3]> = pixels
+        .iter()
+        .map(|p| [p.l.to_bits(), p.a.to_bits(), p.b.to_bits()])
+        .collect();
+    let k = EXTRACT_CLUSTERS.min(distinct.len());
+
+    let mut result = Kmeans::new();
+    for seed in 0..EXTRACT_RUNS {
+        let run_result = get_kmeans_hamerly(k, 20, 5.0, false, pixels, seed);
+        if run_result.score < result.score {
+            result = run_result;
+        }
+    }
+
+    let mut candidates: Vec<AccentCandidate> =
+        Lab::sort_indexed_colors(&result.centroids, &result.indices)
+            .into_iter()
+            .map(|data| AccentCandidate {
+                color: color::gamut_map(data.centroid.into_color(), WorkingSpace::CieLch),
+                coverage: data.percentage,
+            })
+            .filter(|c| c.color.chroma >= MIN_ACCENT_CHROMA && c.coverage >= MIN_COVERAGE)
+            .collect();
+    candidates.sort_by(|a, b| b.score().total_cmp(&a.score()));
+    candidates.truncate(count);
+    candidates
+}
+
+/// Extracts candidate accents from 8 bit RGB(A) pixels, fully transparent pixels are skipped
+/// `n_channels` is 3 for RGB or 4 for RGBA
+pub fn accent_candidates_from_rgb8(
+    pixels: &[u8],
+    n_channels: usize,
+    count: usize,
+) -> Vec<AccentCandidate> {
+    let pixels: Vec<Lab> = pixels
+        .chunks_exact(n_channels)
+        // skip transparent pixels
+        .filter(|p| n_channels < 4 || p[3] >= 128)
+        .map(|p| {
+            Srgb::new(p[0], p[1], p[2])
+                .into_format::<f32>()
+                .into_color()
+        })
+        .collect();
+    accent_candidates(&pixels, count)
+}
@@ -0,0 +1,299 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use palette::Srgb;
+use std::{
+    fmt::{self, Display},
+    path::Path,
+};
+
+use crate::{
+    accent_palette::{AccentPalette, PaletteSet},
+    color::{Backgrounds, ContrastAlgorithm, WorkingSpace},
+};
+
+const HEADER: &str = "GIMP Palette";
+/// suffix of the exported text on the accent
+const FG_SUFFIX: &str = " (fg)";
+/// suffix of the exported accent as text
+const AS_FG_SUFFIX: &str = " (as fg)";
+/// suffixes of the name of a palette exported for the light or dark color scheme
+const SCHEME_SUFFIXES: [(&str, bool); 2] = [(" (Light)", false), (" (Dark)", true)];
+
+/// A color of a GIMP palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct GplColor {
+    pub name: String,
+    pub color: Srgb,
+}
+
+/// A malformed line of a GIMP palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GplError {
+    /// 1-based line number
+    pub line: usize,
+    pub msg: String,
+}
+
+impl Display for GplError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+impl std::error::Error for GplError {}
+
+/// Parses the name and the colors of a GIMP palette
+/// The header is optional and blank lines, comments & unknown keys are skipped
+pub fn parse(data: &str) -> Result<(Option<String>, Vec<GplColor>), GplError> {
+    let mut name = None;
+    let mut colors = vec![];
+
+    for (i, line) in data.trim_start_matches('\u{feff}').lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line == HEADER {
+            continue;
+        }
+        if let Some(n) = line.strip_prefix("Name:") {
+            name = Some(n.trim().to_string()).filter(|n| !n.is_empty());
+            continue;
+        }
+        // Columns: and keys of other tools
+        if line.starts_with(|c: char| c.is_ascii_alphabetic()) && line.contains(':') {
+            continue;
+        }
+
+        let err = |msg: String| GplError { line: i + 1, msg };
+        let mut rest = line;
+        let mut rgb = [0u8; 3];
+        for (c, channel) in rgb.iter_mut().zip(["red", "green", "blue"]) {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let value = &rest[..end];
+            if value.is_empty() {
+                return Err(err(format!("Missing {channel} value")));
+            }
+            *c = value.parse().map_err(|_| {
+                err(format!(
+                    "Invalid {channel} value {value:?}, expected a number from 0 to 255"
+                ))
+            })?;
+            rest = rest[end..].trim_start();
+        }
+
+        colors.push(GplColor {
+            name: rest.to_string(),
+            color: Srgb::new(rgb[0], rgb[1], rgb[2]).into_format(),
+        });
+    }
+
+    Ok((name, colors))
+}
+
+/// Imports a GIMP palette as a palette set with the same entries for light & dark
+/// Colors exported with their roles are grouped again, the missing roles are derived
+/// The roles only belong to the color scheme named by the palette, if it names one
+pub fn import(
+    file_name: &str,
+    data: &str,
+    backgrounds: &[Backgrounds; 2],
+    algorithm: ContrastAlgorithm,
+    space: WorkingSpace,
+) -> anyhow::Result<PaletteSet> {
+    let (name, colors) = parse(data)?;
+    let (name, roles_scheme) = match name.as_deref().and_then(split_scheme) {
+        Some((name, is_dark)) => (Some(name.to_string()), Some(is_dark)),
+        None => (name, None),
+    };
+
+    let mut bgs: Vec<GplColor> = vec![];
+    let mut roles: Vec<(&str, GplColor)> = vec![];
+    for (i, c) in colors.into_iter().enumerate() {
+        if let Some(base) = c.name.strip_suffix(AS_FG_SUFFIX) {
+            roles.push((
+                AS_FG_SUFFIX,
+                GplColor {
+                    name: base.into(),
+                    ..c
+                },
+            ));
+        } else if let Some(base) = c.name.strip_suffix(FG_SUFFIX) {
+            roles.push((
+                FG_SUFFIX,
+                GplColor {
+                    name: base.into(),
+                    ..c
+                },
+            ));
+        } else if c.name.is_empty() || c.name == "Untitled" {
+            bgs.push(GplColor {
+                name: format!("COLOR_{}", i + 1),
+                ..c
+            });
+        } else {
+            bgs.push(c);
+        }
+    }
+    if bgs.is_empty() {
+        anyhow::bail!("The palette doesn't contain any colors");
+    }
+
+    let entries = |is_dark: bool| {
+        let base = backgrounds[is_dark as usize].worst_case(is_dark);
+        bgs.iter()
+            .map(|bg| {
+                let mut entry = AccentPalette::from_bg(
+                    bg.name.clone(),
+                    bg.color,
+                    base,
+                    is_dark,
+                    algorithm,
+                    space,
+                );
+                if roles_scheme.is_some_and(|s| s != is_dark) {
+                    return entry;
+                }
+                for (role, c) in roles.iter().filter(|(_, c)| c.name == bg.name) {
+                    match *role {
+                        AS_FG_SUFFIX => entry.accent_color = c.color,
+                        _ => entry.accent_color_fg = c.color,
+                    }
+                }
+                entry
+            })
+            .collect()
+    };
+
+    let id = Path::new(file_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string();
+    Ok(PaletteSet {
+        name: name.unwrap_or_else(|| id.clone()),
+        id,
+        light: entries(false),
+        dark: entries(true),
+    })
+}
+
+/// The name of a palette exported for one color scheme without its suffix, and the scheme
+fn split_scheme(name: &str) -> Option<(&str, bool)> {
+    SCHEME_SUFFIXES
+        .iter()
+        .find_map(|(suffix, is_dark)| Some((name.strip_suffix(suffix)?, *is_dark)))
+}
+
+/// Writes the entries with their roles as a GIMP palette, one row per entry
+pub fn export(name: &str, palette: &[AccentPalette]) -> String {
+    let mut gpl = format!("{HEADER}\nName: {name}\nColumns: 3\n#\n");
+    for entry in palette {
+        for (c, suffix) in [
+            (entry.accent_color_bg, ""),
+            (entry.accent_color_fg, FG_SUFFIX),
+            (entry.accent_color, AS_FG_SUFFIX),
+        ] {
+            let (r, g, b) = c.into_format::<u8>().into_components();
+            gpl += &format!("{r:3} {g:3} {b:3}\t{}{suffix}\n", entry.name);
+        }
+    }
+    gpl
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color;
+    use palette::{IntoColor, Lch};
+
+    #[test]
+    fn parse_tolerates_comments_and_missing_header() {
+        let (name, colors) =
+            parse(" 53 132 228\tBlue\r\n# comment\n\n  0   0 0  Black Night \n").unwrap();
+        assert_eq!(name, None);
+        assert_eq!(colors.len(), 2);
+        assert_eq!(colors[0].name, "Blue");
+        assert_eq!(color::hex_from_srgb(colors[0].color), "3584e4");
+        assert_eq!(colors[1].name, "Black Night");
+    }
+
+    #[test]
+    fn parse_reports_malformed_line() {
+        let err = parse("GIMP Palette\nName: Test\n255 0\n").unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(err.msg.contains("blue"));
+
+        let err = parse("GIMP Palette\n256 0 0 Red\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.msg.contains("\"256\""));
+    }
+
+    #[test]
+    fn import_derives_missing_roles() {
+        let set = import(
+            "brand.gpl",
+            "GIMP Palette\nName: Brand\n 53 132 228\tBlue\n",
+            &[Backgrounds::adwaita(false), Backgrounds::adwaita(true)],
+            ContrastAlgorithm::Wcag2,
+            WorkingSpace::CieLch,
+        )
+        .unwrap();
+        assert_eq!(set.id, "brand");
+        assert_eq!(set.name, "Brand");
+        assert_eq!(set.light.len(), 1);
+        assert_eq!(set.dark.len(), 1);
+
+        let light = &set.light[0];
+        let white: Lch = Srgb::new(1.0, 1.0, 1.0).into_color();
+        assert!(ContrastAlgorithm::Wcag2.contrast(light.accent_color.into_color(), white) >= 6.99);
+        assert_eq!(color::hex_from_srgb(light.accent_color_bg), "3584e4");
+        assert_eq!(color::hex_from_srgb(light.accent_color_fg), "000000");
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let backgrounds = [Backgrounds::adwaita(false), Backgrounds::adwaita(true)];
+        // the text colors of each scheme fail in the other one
+        for (is_dark, accent_color) in [(false, "#1c71d8"), (true, "#78aeed")] {
+            let entry = AccentPalette {
+                name: "BLUE".into(),
+                accent_color: color::srgb_from_hex(accent_color).unwrap(),
+                accent_color_bg: color::srgb_from_hex("#3584e4").unwrap(),
+                accent_color_fg: color::srgb_from_hex("#ffffff").unwrap(),
+            };
+            let scheme = if is_dark { "Dark" } else { "Light" };
+            let gpl = export(&format!("Test ({scheme})"), std::slice::from_ref(&entry));
+            let set = import(
+                "test.gpl",
+                &gpl,
+                &backgrounds,
+                ContrastAlgorithm::Wcag2,
+                WorkingSpace::CieLch,
+            )
+            .unwrap();
+            assert_eq!(set.name, "Test");
+
+            let imported = &set.palette(is_dark)[0];
+            assert_eq!(imported.name, entry.name);
+            for (a, b) in [
+                (imported.accent_color, entry.accent_color),
+                (imported.accent_color_bg, entry.accent_color_bg),
+                (imported.accent_color_fg, entry.accent_color_fg),
+            ] {
+                assert_eq!(color::hex_from_srgb(a), color::hex_from_srgb(b));
+            }
+
+            // the roles of the other scheme are derived
+            let other = &set.palette(!is_dark)[0];
+            assert_eq!(
+                color::hex_from_srgb(other.accent_color_bg),
+                color::hex_from_srgb(entry.accent_color_bg)
+            );
+            let base = backgrounds[!is_dark as usize].worst_case(!is_dark);
+            let (text_target, _) = ContrastAlgorithm::Wcag2.accent_contrast(!is_dark);
+            let contrast = ContrastAlgorithm::Wcag2.contrast(other.accent_color.into_color(), base);
+            assert!(
+                contrast >= text_target * (1.0 - 1e-3),
+                "{scheme} {contrast}"
+            );
+        }
+    }
+}
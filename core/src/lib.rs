@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+//! The color logic of Hue Chroma Accent, without GTK
+//!
+//! - [`color`] derives accent colors with enough contrast against a background, with WCAG 2 or APCA,
+//!   keeping hue & chroma in CIE Lch or Oklch
+//! - [`accent_palette`] matches colors to palettes and reads & writes them as TOML, JSON,
+//!   GIMP palettes ([`gpl`]) and Adobe Swatch Exchange files ([`ase`])
+//! - [`css_color`] parses colors in any CSS notation
+//! - [`extract`] finds candidate accents in the pixels of an image
+//!
+//! ```
+//! use hue_chroma_accent_core::color::{self, Accent, Backgrounds, ContrastAlgorithm, WorkingSpace};
+//! use palette::IntoColor;
+//!
+//! let blue = color::srgb_from_hex("#3584e4").unwrap();
+//! let accent = Accent::derive(
+//!     blue.into_color(),
+//!     Backgrounds::adwaita(true).worst_case(true),
+//!     true,
+//!     ContrastAlgorithm::Wcag2,
+//!     WorkingSpace::CieLch,
+//! );
+//! println!("{}", accent.to_css());
+//! ```
+
+pub mod accent_palette;
+pub mod ase;
+pub mod color;
+pub mod css_color;
+pub mod extract;
+pub mod gpl;
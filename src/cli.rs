@@ -0,0 +1,299 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use hue_chroma_accent_core::{
+    accent_palette::{self, DistanceMetric, PaletteSet},
+    color::{self, Accent, ContrastAlgorithm, WorkingSpace},
+};
+use palette::{IntoColor, Srgb};
+
+use crate::{palette_store, util};
+
+const USAGE: &str = "Usage: hue-chroma-accent derive --color <#rrggbb> [OPTIONS]
+
+Options:
+    --color <#rrggbb>       the accent color to derive from
+    --scheme <light|dark>   the color scheme, light by default
+    --palette <id|name>     match the color to a palette instead of deriving it
+    --contrast <wcag2|apca> the contrast algorithm, wcag2 by default
+    --space <cielch|oklch>  the working space, cielch by default
+    --metric <cie76|cie94|ciede2000|oklab>
+                            the palette matching metric, ciede2000 by default
+    --format <css|json>     the output format, css by default";
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Format {
+    Css,
+    Json,
+}
+
+/// Arguments of `hue-chroma-accent derive`
+#[derive(Clone, PartialEq, Debug)]
+pub struct DeriveArgs {
+    pub color: Srgb,
+    pub is_dark: bool,
+    pub palette: Option<String>,
+    pub algorithm: ContrastAlgorithm,
+    pub space: WorkingSpace,
+    pub metric: DistanceMetric,
+    pub format: Format,
+}
+
+impl DeriveArgs {
+    /// Parses the arguments following `derive`, both `--key value` and `--key=value` are accepted
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> anyhow::Result<Self> {
+        let mut source = None;
+        let mut derive_args = Self {
+            color: Srgb::default(),
+            is_dark: false,
+            palette: None,
+            algorithm: ContrastAlgorithm::default(),
+            space: WorkingSpace::default(),
+            metric: DistanceMetric::default(),
+            format: Format::Css,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (key, value) = match arg.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("Missing value for {}", arg))?;
+                    (arg, value)
+                }
+            };
+            let invalid = || anyhow::anyhow!("Invalid value {:?} for {}", value, key);
+
+            match key.as_str() {
+                "--color" => source = Some(color::srgb_from_hex(&value)?),
+                "--scheme" => {
+                    derive_args.is_dark = match value.as_str() {
+                        "light" => false,
+                        "dark" => true,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "--palette" => derive_args.palette = Some(value.clone()),
+                "--contrast" => {
+                    derive_args.algorithm = match value.as_str() {
+                        "wcag2" => ContrastAlgorithm::Wcag2,
+                        "apca" => ContrastAlgorithm::Apca,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "--space" => {
+                    derive_args.space = match value.as_str() {
+                        "cielch" => WorkingSpace::CieLch,
+                        "oklch" => WorkingSpace::Oklch,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "--metric" => {
+                    derive_args.metric = match value.as_str() {
+                        "cie76" => DistanceMetric::Cie76,
+                        "cie94" => DistanceMetric::Cie94,
+                        "ciede2000" => DistanceMetric::Ciede2000,
+                        "oklab" => DistanceMetric::Oklab,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "--format" => {
+                    derive_args.format = match value.as_str() {
+                        "css" => Format::Css,
+                        "json" => Format::Json,
+                        _ => return Err(invalid()),
+                    }
+                }
+                _ => anyhow::bail!("Unknown option {}", key),
+            }
+        }
+
+        derive_args.color = source.ok_or_else(|| anyhow::anyhow!("Missing --color"))?;
+        Ok(derive_args)
+    }
+}
+
+/// Runs `hue-chroma-accent derive` with the arguments following it and returns the exit code
+/// Nothing here needs a display, so it works in CI
+pub fn run(args: Vec<String>) -> i32 {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{USAGE}");
+        return 0;
+    }
+
+    let res = DeriveArgs::parse(args).and_then(|args| {
+        let palette_set = match &args.palette {
+            Some(palette) => Some(find_palette(palette)?),
+            None => None,
+        };
+        derive(&args, palette_set.as_ref())
+    });
+    match res {
+        Ok(out) => {
+            println!("{out}");
+            0
+        }
+        Err(err) => {
+            eprintln!("{:#}\n\n{USAGE}", err);
+            2
+        }
+    }
+}
+
+/// Finds a bundled or user palette by id or name
+fn find_palette(palette: &str) -> anyhow::Result<PaletteSet> {
+    let palette_sets = palette_store::load_palettes();
+    let ids: Vec<&str> = palette_sets.iter().map(|s| s.id.as_str()).collect();
+    let ids = ids.join(", ");
+
+    palette_sets
+        .iter()
+        .find(|s| s.id == palette || s.name.eq_ignore_ascii_case(palette))
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Unknown palette {:?}, expected one of {}", palette, ids))
+}
+
+/// The accent like in the accent editor, matched to the palette if there is one or derived otherwise
+/// Derived accents use the backgrounds of the installed libadwaita, like the editor
+pub fn derive(args: &DeriveArgs, palette_set: Option<&PaletteSet>) -> anyhow::Result<String> {
+    let c = args.color.into_color();
+    let accent = match palette_set {
+        Some(palette_set) => {
+            let palette = palette_set.palette(args.is_dark);
+            let i = accent_palette::nearest(palette, c, args.metric).ok_or_else(|| {
+                anyhow::anyhow!("Palette {:?} doesn't have any colors", palette_set.name)
+            })?;
+            Accent::from(&palette[i])
+        }
+        None => Accent::derive(
+            c,
+            util::adwaita_backgrounds(args.is_dark).worst_case(args.is_dark),
+            args.is_dark,
+            args.algorithm,
+            args.space,
+        ),
+    };
+
+    Ok(match args.format {
+        Format::Css => accent.to_css().trim().to_string(),
+        Format::Json => {
+            let hex = |c| format!("#{}", color::hex_from_srgb(c));
+            serde_json::to_string_pretty(&serde_json::json!({
+                "accent_color": hex(accent.accent),
+                "accent_bg_color": hex(accent.accent_bg),
+                "accent_fg_color": hex(accent.accent_fg),
+            }))?
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hue_chroma_accent_core::color::Backgrounds;
+    use palette::Lch;
+
+    fn args(args: &[&str]) -> anyhow::Result<DeriveArgs> {
+        DeriveArgs::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn parse_options() {
+        let parsed = args(&[
+            "--color",
+            "#3584e4",
+            "--scheme=dark",
+            "--palette",
+            "gnome",
+            "--format",
+            "json",
+        ])
+        .unwrap();
+        assert_eq!(color::hex_from_srgb(parsed.color), "3584e4");
+        assert!(parsed.is_dark);
+        assert_eq!(parsed.palette.as_deref(), Some("gnome"));
+        assert_eq!(parsed.format, Format::Json);
+        assert_eq!(parsed.algorithm, ContrastAlgorithm::Wcag2);
+
+        assert!(args(&["--scheme", "dark"]).is_err());
+        assert!(args(&["--color", "#3584e4", "--scheme", "dim"]).is_err());
+        assert!(args(&["--color", "#3584e4", "--format"]).is_err());
+        assert!(args(&["--color", "#3584e4", "--colour", "red"]).is_err());
+    }
+
+    #[test]
+    fn derive_matches_palette() {
+        let palette_set = PaletteSet::parse(
+            "gnome.toml",
+            include_str!("../data/resources/palettes/gnome.toml"),
+        )
+        .unwrap();
+        let parsed = args(&["--color", "#3584e4", "--scheme", "dark", "--format", "json"]).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&derive(&parsed, Some(&palette_set)).unwrap()).unwrap();
+
+        let blue = palette_set
+            .dark
+            .iter()
+            .find(|p| p.name == "BLUE_DARK")
+            .unwrap();
+        assert_eq!(
+            json["accent_bg_color"],
+            format!("#{}", color::hex_from_srgb(blue.accent_color_bg))
+        );
+    }
+
+    #[test]
+    fn derive_meets_requested_contrast() {
+        for (scheme, is_dark) in [("light", false), ("dark", true)] {
+            for (contrast, algorithm) in [
+                ("wcag2", ContrastAlgorithm::Wcag2),
+                ("apca", ContrastAlgorithm::Apca),
+            ] {
+                let parsed = args(&[
+                    "--color",
+                    "#e01b24",
+                    "--scheme",
+                    scheme,
+                    "--contrast",
+                    contrast,
+                    "--space",
+                    "oklch",
+                    "--format",
+                    "json",
+                ])
+                .unwrap();
+                let json: serde_json::Value =
+                    serde_json::from_str(&derive(&parsed, None).unwrap()).unwrap();
+                let role = |key: &str| -> Lch {
+                    color::srgb_from_hex(json[key].as_str().unwrap())
+                        .unwrap()
+                        .into_color()
+                };
+
+                let base = Backgrounds::adwaita(is_dark).worst_case(is_dark);
+                let (text_target, bg_target) = algorithm.accent_contrast(is_dark);
+                for (key, target) in [
+                    ("accent_color", text_target),
+                    ("accent_bg_color", bg_target),
+                ] {
+                    let contrast = algorithm.contrast(role(key), base);
+                    assert!(
+                        contrast >= target * (1.0 - 1e-3),
+                        "{scheme} {key} {}",
+                        algorithm.format(contrast)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn derive_without_palette() {
+        let parsed = args(&["--color", "#3584e4"]).unwrap();
+        let css = derive(&parsed, None).unwrap();
+        assert!(css.starts_with("@define-color accent_color #"));
+        assert_eq!(css.lines().count(), 3);
+    }
+}
@@ -1,22 +1,74 @@
 // SPDX-License-Identifier: MPL-2.0-only
 
 use adw::{
-    gtk::{glib, subclass::prelude::*, Box, ColorButton, CssProvider, Switch, ToggleButton},
+    gtk::{
+        gio, glib, subclass::prelude::*, Box, ColorButton, CssProvider, DropDown,
+        FileChooserNative, Switch, ToggleButton,
+    },
     StyleManager,
 };
+use hue_chroma_accent_core::{
+    accent_palette::{DistanceMetric, PaletteSet},
+    color::{Accent, Backgrounds, ContrastAlgorithm, WorkingSpace},
+};
 use once_cell::sync::OnceCell;
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use super::{EditSource, EditorState};
+use crate::{
+    components::{
+        accent_swatches::AccentSwatches, color_readout::ColorReadout,
+        contrast_report::ContrastReport, lch_sliders::LchSliders, tonal_scale::TonalScale,
+    },
+    history::History,
+};
 
 // Object holding the state
 #[derive(Default)]
 pub struct AccentEditor {
     pub css_provider: Rc<OnceCell<CssProvider>>,
+    /// the style sheet of the app, the accent is appended to it on every change
+    pub base_css: Rc<OnceCell<String>>,
+    pub palette_css_provider: Rc<OnceCell<CssProvider>>,
     pub color_editor: Rc<OnceCell<Box>>,
     pub accent_button: Rc<OnceCell<ColorButton>>,
+    pub lch_sliders: Rc<OnceCell<LchSliders>>,
+    pub accent_swatches: Rc<OnceCell<AccentSwatches>>,
     pub use_palette_switch: Rc<OnceCell<Switch>>,
+    pub gtk3_switch: Rc<OnceCell<Switch>>,
+    pub metric_dropdown: Rc<OnceCell<DropDown>>,
+    pub palette_set_dropdown: Rc<OnceCell<DropDown>>,
+    pub file_chooser: Rc<RefCell<Option<FileChooserNative>>>,
     pub palette_buttons: Rc<RefCell<Vec<ToggleButton>>>,
     pub palette_box: Rc<OnceCell<Box>>,
+    pub tonal_scale: Rc<OnceCell<TonalScale>>,
+    pub contrast_report: Rc<OnceCell<ContrastReport>>,
+    pub color_readout: Rc<OnceCell<ColorReadout>>,
+    pub wallpaper_settings: Rc<OnceCell<gio::Settings>>,
+    pub wallpaper_handler: Rc<RefCell<Option<glib::SignalHandlerId>>>,
+    /// counts the wallpapers sent off to be decoded, only the latest one is applied
+    pub wallpaper_request: Rc<Cell<u32>>,
+    pub palette_sets: Rc<RefCell<Vec<PaletteSet>>>,
+    pub palette_set: Rc<Cell<usize>>,
     pub style_manager: Rc<OnceCell<StyleManager>>,
+    /// the backgrounds of the light & dark color scheme
+    pub backgrounds: Rc<OnceCell<[Backgrounds; 2]>>,
+    pub accent: Rc<RefCell<Option<Accent>>>,
+    pub contrast_algorithm: Rc<Cell<ContrastAlgorithm>>,
+    pub working_space: Rc<Cell<WorkingSpace>>,
+    pub distance_metric: Rc<Cell<DistanceMetric>>,
+    /// the system, light & dark toggles, if the system supports color schemes
+    pub color_scheme_buttons: Rc<OnceCell<[ToggleButton; 3]>>,
+    pub history: Rc<RefCell<History<EditorState, EditSource>>>,
+    /// the control that is changing the accent, while it does
+    pub edit_source: Rc<Cell<Option<EditSource>>>,
+    /// set while a state of the history is restored, so it isn't recorded again
+    pub restoring: Rc<Cell<bool>>,
+    pub undo_action: Rc<OnceCell<gio::SimpleAction>>,
+    pub redo_action: Rc<OnceCell<gio::SimpleAction>>,
 }
 
 // The central trait for subclassing a GObject
@@ -8,24 +8,57 @@ use adw::{
     gtk::{
         self,
         gdk::{self, RGBA},
+        gio,
         glib::{self, closure_local},
         subclass::prelude::*,
-        Align, Box, Button, ColorButton, CssProvider, Entry, Label, MessageDialog, Orientation,
-        ScrolledWindow, TextView, ToggleButton, Window,
+        Align, Box, Button, ColorButton, CssProvider, DropDown, Entry, FileChooserAction,
+        FileChooserNative, FileFilter, Label, Orientation, ResponseType, ScrolledWindow,
+        StringList, StyleContext, TextView, ToggleButton, Window,
     },
     ColorScheme,
 };
+use anyhow::Context;
 use cascade::cascade;
-use palette::{white_point::D65, IntoColor, Lch, Srgb};
+use hue_chroma_accent_core::{
+    accent_palette::{self, AccentPalette, DistanceMetric, PaletteSet},
+    color::{self, Accent, ContrastAlgorithm, WorkingSpace},
+};
+use palette::{IntoColor, Srgb};
 use relm4_macros::view;
-use std::fmt::Display;
+use std::{fmt::Display, path::Path, time::Instant};
 
 use crate::{
-    accent_palette,
+    components::{
+        accent_swatches::AccentSwatches, color_readout::ColorReadout,
+        contrast_report::ContrastReport, lch_sliders::LchSliders, palette_editor::PaletteEditor,
+        tonal_scale::TonalScale, widget_gallery::WidgetGallery,
+    },
+    gtk_css, palette_store,
     util::{self, SRGB},
+    wallpaper,
 };
 mod imp;
 
+/// number of candidates shown when extracting accents from an image
+const EXTRACTED_ACCENTS: usize = 5;
+
+/// What undo & redo step through
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct EditorState {
+    /// the color of the color button
+    pub color: Srgb,
+    pub use_palette: bool,
+    /// the selected entry of the palette, when it is used
+    pub palette_entry: Option<usize>,
+    pub color_scheme: ColorScheme,
+}
+
+/// The controls whose quick, consecutive changes are undone at once
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum EditSource {
+    LchSliders,
+}
+
 glib::wrapper! {
     pub struct AccentEditor(ObjectSubclass<imp::AccentEditor>)
         @extends gtk::Box, gtk::Widget,
@@ -49,6 +82,65 @@ impl AccentEditor {
             ..set_use_alpha(true);
         };
 
+        let lch_sliders = LchSliders::new(
+            glib::clone!(@weak accent_color_button, @weak imp.edit_source as edit_source => move |c| {
+                let c: Srgb = c.into_color();
+                edit_source.set(Some(EditSource::LchSliders));
+                accent_color_button.set_rgba(&SRGB(c).into());
+                edit_source.set(None);
+            }),
+        );
+
+        let color_entry = cascade! {
+            Entry::new();
+            ..set_placeholder_text(Some("CSS color, e.g. oklch(60% 0.15 250)"));
+            ..set_width_chars(28);
+        };
+        color_entry.connect_changed(|entry| {
+            let text = entry.text();
+            match util::parse_color(&text) {
+                Err(err) if !text.is_empty() => {
+                    entry.add_css_class("error");
+                    entry.set_tooltip_text(Some(&err.to_string()));
+                }
+                _ => {
+                    entry.remove_css_class("error");
+                    entry.set_tooltip_text(None);
+                }
+            }
+        });
+        color_entry.connect_activate(glib::clone!(@weak accent_color_button => move |entry| {
+            match util::parse_color(&entry.text()) {
+                Ok(c) => accent_color_button.set_rgba(&c.into()),
+                Err(err) => {
+                    // select the offending character
+                    let position = err.position as i32;
+                    entry.select_region(position, position + 1);
+                }
+            }
+        }));
+
+        let accent_swatches =
+            AccentSwatches::new(glib::clone!(@weak accent_color_button => move |c| {
+                accent_color_button.set_rgba(&SRGB(c).into());
+            }));
+
+        let palette_sets = palette_store::load_palettes();
+        let palette_names: Vec<&str> = palette_sets.iter().map(|s| s.name.as_str()).collect();
+        let palette_set_dropdown = DropDown::from_strings(&palette_names);
+
+        let palette_css_provider = CssProvider::new();
+        StyleContext::add_provider_for_display(
+            &gdk::Display::default().expect("Error initializing GTK CSS provider."),
+            &palette_css_provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+
+        let metric_dropdown = cascade! {
+            DropDown::from_strings(&["CIE76", "CIE94", "CIEDE2000", "Oklab"]);
+            ..set_selected(2);
+        };
+
         view! {
             palette_box = &Box {
                 set_orientation: Orientation::Horizontal,
@@ -61,8 +153,31 @@ impl AccentEditor {
             }
         };
 
-        let accented_button = Button::with_label("Demo Button");
-        accented_button.add_css_class("suggested-action");
+        let backgrounds = [
+            util::adwaita_backgrounds(false),
+            util::adwaita_backgrounds(true),
+        ];
+
+        let tonal_scale = TonalScale::new(
+            backgrounds,
+            glib::clone!(@weak self_ => move |mut palette_set| {
+                match palette_store::save(&mut palette_set) {
+                    Ok(path) => {
+                        log::info!("Saved tonal scale to {}", path.display());
+                        self_.reload_palettes(&palette_set.id);
+                    }
+                    Err(err) => {
+                        log::warn!("Failed to save tonal scale, {}", &err);
+                        util::show_error(&self_, "Failed to Save Tonal Scale", &err);
+                    }
+                }
+            }),
+        );
+
+        let contrast_report = ContrastReport::new();
+        let color_readout = ColorReadout::new();
+
+        let widget_gallery = WidgetGallery::new();
 
         view! {
             inner = Box {
@@ -91,6 +206,34 @@ impl AccentEditor {
                     append: use_palette_label =  &Label {
                         set_text: "Use Palette",
                     },
+
+                    append: &palette_set_dropdown,
+
+                    append: edit_palette_button = &Button {
+                        set_icon_name: "document-edit-symbolic",
+                        set_tooltip_text: Some("Edit Palette"),
+                        set_action_name: Some("editor.edit-palette"),
+                    },
+                    append: new_palette_button = &Button {
+                        set_icon_name: "list-add-symbolic",
+                        set_tooltip_text: Some("New Palette"),
+                        set_action_name: Some("editor.new-palette"),
+                    },
+                    append: import_palette_button = &Button {
+                        set_icon_name: "document-open-symbolic",
+                        set_tooltip_text: Some("Import Palette"),
+                        set_action_name: Some("editor.import-palette"),
+                    },
+                    append: export_palette_button = &Button {
+                        set_icon_name: "document-save-symbolic",
+                        set_tooltip_text: Some("Export Palette"),
+                        set_action_name: Some("editor.export-palette"),
+                    },
+                    append: extract_palette_button = &Button {
+                        set_icon_name: "image-x-generic-symbolic",
+                        set_tooltip_text: Some("Extract Accents from Image"),
+                        set_action_name: Some("editor.extract-palette"),
+                    },
                 },
 
                 // color picker
@@ -105,14 +248,81 @@ impl AccentEditor {
 
                     append: &accent_color_button,
 
-                    append: label =  &Label {
-                        set_text: "Choose Color",
+                    append: &color_entry,
+
+                    append: follow_wallpaper_switch = &Switch {
+                        set_halign: Align::End,
+                        set_hexpand: true,
+                    },
+                    append: follow_wallpaper_label = &Label {
+                        set_text: "Follow Wallpaper",
+                    },
+                },
+                append: &lch_sliders,
+                append: &accent_swatches,
+                // contrast algorithm
+                append: contrast_box = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 4,
+                    set_margin_top: 4,
+                    set_margin_bottom: 4,
+                    set_margin_start: 4,
+                    set_margin_end: 4,
+                    set_hexpand: true,
+
+                    append: wcag_toggle = &ToggleButton {
+                        set_child: Some(&Label::new(Some("WCAG 2"))),
+                        set_active: true,
+                    },
+                    append: apca_toggle = &ToggleButton {
+                        set_child: Some(&Label::new(Some("APCA"))),
+                        set_group: Some(&wcag_toggle),
+                    },
+                },
+                // working space
+                append: space_box = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 4,
+                    set_margin_top: 4,
+                    set_margin_bottom: 4,
+                    set_margin_start: 4,
+                    set_margin_end: 4,
+                    set_hexpand: true,
+
+                    append: cie_lch_toggle = &ToggleButton {
+                        set_child: Some(&Label::new(Some("CIE Lch"))),
+                        set_active: true,
+                    },
+                    append: oklch_toggle = &ToggleButton {
+                        set_child: Some(&Label::new(Some("Oklch"))),
+                        set_group: Some(&cie_lch_toggle),
+                    },
+                },
+                append: metric_box = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 4,
+                    set_margin_top: 4,
+                    set_margin_bottom: 4,
+                    set_margin_start: 4,
+                    set_margin_end: 4,
+                    set_hexpand: true,
+
+                    append: &metric_dropdown,
+                    append: metric_label = &Label {
+                        set_text: "Palette Matching",
                     },
                 },
                 // palette
                 append: &palette_box,
 
-                append: demo = &Box {
+                append: &tonal_scale,
+
+                append: &widget_gallery,
+
+                append: &contrast_report,
+                append: &color_readout,
+
+                append: apply_box = &Box {
                     set_orientation: Orientation::Horizontal,
                     set_spacing: 4,
                     set_margin_top: 4,
@@ -121,13 +331,30 @@ impl AccentEditor {
                     set_margin_end: 4,
                     set_hexpand: true,
 
-                    append: &accented_button,
-                    append: accent_color_label = &Label {
-                        set_text: "Accent Color as text",
-                        add_css_class: "accented-text",
-                        add_css_class: "title-3"
+                    append: undo_button = &Button {
+                        set_icon_name: "edit-undo-symbolic",
+                        set_tooltip_text: Some("Undo"),
+                        set_action_name: Some("editor.undo"),
+                    },
+                    append: redo_button = &Button {
+                        set_icon_name: "edit-redo-symbolic",
+                        set_tooltip_text: Some("Redo"),
+                        set_action_name: Some("editor.redo"),
+                    },
+                    append: apply_button = &Button {
+                        set_label: "Apply",
+                        set_action_name: Some("editor.apply"),
+                    },
+                    append: remove_button = &Button {
+                        set_label: "Remove",
+                        set_action_name: Some("editor.remove"),
+                    },
+                    append: gtk3_switch = &Switch {
+                        set_active: true,
+                    },
+                    append: gtk3_label = &Label {
+                        set_text: "Include GTK 3",
                     },
-
                 },
             }
 
@@ -140,6 +367,11 @@ impl AccentEditor {
                 glib::clone!(@weak self_ => move |style_manager| {
                     self_.set_palette_buttons(style_manager.is_dark());
                     self_.set_accent();
+                    // the dark wallpaper may be different
+                    let imp = imp::AccentEditor::from_instance(&self_);
+                    if imp.wallpaper_handler.borrow().is_some() {
+                        self_.follow_wallpaper();
+                    }
                 }),
             );
 
@@ -183,6 +415,9 @@ impl AccentEditor {
                 }
             };
             color_scheme_box.insert_after(&inner, Some(&palette_box));
+            imp.color_scheme_buttons
+                .set([system_color_scheme, light_color_scheme, dark_color_scheme])
+                .unwrap();
         }
 
         let scroll_window = ScrolledWindow::builder()
@@ -196,22 +431,404 @@ impl AccentEditor {
             self_.set_accent();
             gtk::Inhibit(false)
         }));
+        match wallpaper::background_settings() {
+            Some(settings) => {
+                follow_wallpaper_switch.connect_state_set(
+                    glib::clone!(@weak self_ => @default-return gtk::Inhibit(false), move |_, state| {
+                        self_.set_follow_wallpaper(state);
+                        gtk::Inhibit(false)
+                    }),
+                );
+                imp.wallpaper_settings.set(settings).unwrap();
+            }
+            None => {
+                follow_wallpaper_switch.set_sensitive(false);
+                follow_wallpaper_switch
+                    .set_tooltip_text(Some("The GNOME background settings aren't available"));
+            }
+        }
+        wcag_toggle.connect_toggled(glib::clone!(@weak self_ => move |t| {
+            if t.is_active() {
+                self_.set_contrast_algorithm(ContrastAlgorithm::Wcag2);
+            }
+        }));
+        apca_toggle.connect_toggled(glib::clone!(@weak self_ => move |t| {
+            if t.is_active() {
+                self_.set_contrast_algorithm(ContrastAlgorithm::Apca);
+            }
+        }));
+        cie_lch_toggle.connect_toggled(glib::clone!(@weak self_ => move |t| {
+            if t.is_active() {
+                self_.set_working_space(WorkingSpace::CieLch);
+            }
+        }));
+        oklch_toggle.connect_toggled(glib::clone!(@weak self_ => move |t| {
+            if t.is_active() {
+                self_.set_working_space(WorkingSpace::Oklch);
+            }
+        }));
+        metric_dropdown.connect_selected_notify(glib::clone!(@weak self_ => move |d| {
+            let metric = match d.selected() {
+                0 => DistanceMetric::Cie76,
+                1 => DistanceMetric::Cie94,
+                3 => DistanceMetric::Oklab,
+                _ => DistanceMetric::Ciede2000,
+            };
+            self_.set_distance_metric(metric);
+        }));
+        palette_set_dropdown.connect_selected_notify(glib::clone!(@weak self_ => move |d| {
+            self_.set_palette_set(d.selected() as usize);
+        }));
         let is_dark = style_manager.is_dark();
+        imp.base_css.set(provider.to_str().to_string()).unwrap();
         imp.css_provider.set(provider).unwrap();
+        imp.palette_css_provider.set(palette_css_provider).unwrap();
+        imp.palette_sets.replace(palette_sets);
+        imp.palette_set_dropdown.set(palette_set_dropdown).unwrap();
         imp.accent_button.set(accent_color_button).unwrap();
+        imp.lch_sliders.set(lch_sliders).unwrap();
+        imp.accent_swatches.set(accent_swatches).unwrap();
         imp.color_editor.set(inner).unwrap();
         imp.palette_buttons.replace(vec![]);
         imp.palette_box.set(palette_box).unwrap();
+        imp.tonal_scale.set(tonal_scale).unwrap();
+        imp.contrast_report.set(contrast_report).unwrap();
+        imp.color_readout.set(color_readout).unwrap();
         imp.style_manager.set(style_manager).unwrap();
+        imp.backgrounds.set(backgrounds).unwrap();
         imp.use_palette_switch.set(use_palette_switch).unwrap();
+        imp.gtk3_switch.set(gtk3_switch).unwrap();
+        imp.metric_dropdown.set(metric_dropdown).unwrap();
         // set widget state
 
         self_.set_palette_buttons(is_dark);
         self_.connect_color_button();
+        self_.setup_actions();
 
         self_
     }
 
+    fn setup_actions(&self) {
+        let actions = gio::SimpleActionGroup::new();
+
+        // Apply
+        let action_apply = gio::SimpleAction::new("apply", None);
+        action_apply.connect_activate(glib::clone!(@weak self as self_ => move |_, _| {
+            self_.apply();
+        }));
+        actions.add_action(&action_apply);
+
+        // Remove
+        let action_remove = gio::SimpleAction::new("remove", None);
+        action_remove.connect_activate(glib::clone!(@weak self as self_ => move |_, _| {
+            self_.remove();
+        }));
+        actions.add_action(&action_remove);
+
+        // Edit Palette
+        let action_edit_palette = gio::SimpleAction::new("edit-palette", None);
+        action_edit_palette.connect_activate(glib::clone!(@weak self as self_ => move |_, _| {
+            let imp = imp::AccentEditor::from_instance(&self_);
+            let palette_set = imp.palette_sets.borrow().get(imp.palette_set.get()).cloned();
+            if let Some(palette_set) = palette_set {
+                self_.open_palette_editor(palette_set);
+            }
+        }));
+        actions.add_action(&action_edit_palette);
+
+        // New Palette
+        let action_new_palette = gio::SimpleAction::new("new-palette", None);
+        action_new_palette.connect_activate(glib::clone!(@weak self as self_ => move |_, _| {
+            self_.open_palette_editor(PaletteSet {
+                id: String::new(),
+                name: "Custom".into(),
+                light: vec![],
+                dark: vec![],
+            });
+        }));
+        actions.add_action(&action_new_palette);
+
+        // Import Palette
+        let action_import_palette = gio::SimpleAction::new("import-palette", None);
+        action_import_palette.connect_activate(glib::clone!(@weak self as self_ => move |_, _| {
+            self_.choose_palette_file(FileChooserAction::Open);
+        }));
+        actions.add_action(&action_import_palette);
+
+        // Export Palette
+        let action_export_palette = gio::SimpleAction::new("export-palette", None);
+        action_export_palette.connect_activate(glib::clone!(@weak self as self_ => move |_, _| {
+            self_.choose_palette_file(FileChooserAction::Save);
+        }));
+        actions.add_action(&action_export_palette);
+
+        // Extract from Image
+        let action_extract_palette = gio::SimpleAction::new("extract-palette", None);
+        action_extract_palette.connect_activate(glib::clone!(@weak self as self_ => move |_, _| {
+            self_.choose_image();
+        }));
+        actions.add_action(&action_extract_palette);
+
+        // Undo & Redo
+        let action_undo = gio::SimpleAction::new("undo", None);
+        action_undo.connect_activate(glib::clone!(@weak self as self_ => move |_, _| {
+            let imp = imp::AccentEditor::from_instance(&self_);
+            let state = imp.history.borrow_mut().undo();
+            if let Some(state) = state {
+                self_.restore(state);
+            }
+        }));
+        actions.add_action(&action_undo);
+
+        let action_redo = gio::SimpleAction::new("redo", None);
+        action_redo.connect_activate(glib::clone!(@weak self as self_ => move |_, _| {
+            let imp = imp::AccentEditor::from_instance(&self_);
+            let state = imp.history.borrow_mut().redo();
+            if let Some(state) = state {
+                self_.restore(state);
+            }
+        }));
+        actions.add_action(&action_redo);
+
+        let imp = imp::AccentEditor::from_instance(&self);
+        imp.undo_action.set(action_undo).unwrap();
+        imp.redo_action.set(action_redo).unwrap();
+        self.update_history_actions();
+
+        self.insert_action_group("editor", Some(&actions));
+    }
+
+    fn open_palette_editor(&self, palette_set: PaletteSet) {
+        let imp = imp::AccentEditor::from_instance(&self);
+
+        let palette_editor = PaletteEditor::new(
+            palette_set,
+            *imp.backgrounds.get().unwrap(),
+            imp.style_manager.get().unwrap().is_dark(),
+            imp.contrast_algorithm.get(),
+            glib::clone!(@weak self as self_ => move |palette_set: &PaletteSet| {
+                self_.reload_palettes(&palette_set.id);
+            }),
+        );
+        let window = Window::builder()
+            .title("Edit Palette")
+            .modal(true)
+            .default_width(600)
+            .default_height(400)
+            .child(&palette_editor)
+            .build();
+        window.set_transient_for(self.root_window().as_ref());
+        window.present();
+    }
+
+    fn root_window(&self) -> Option<Window> {
+        self.root().and_then(|r| r.downcast::<Window>().ok())
+    }
+
+    fn choose_palette_file(&self, action: FileChooserAction) {
+        let imp = imp::AccentEditor::from_instance(&self);
+
+        let filter = cascade! {
+            FileFilter::new();
+            ..set_name(Some("Palettes (.gpl, .ase, .toml, .json)"));
+            ..add_pattern("*.gpl");
+            ..add_pattern("*.ase");
+            ..add_pattern("*.toml");
+            ..add_pattern("*.json");
+        };
+        match action {
+            FileChooserAction::Save => {
+                let name = imp
+                    .palette_sets
+                    .borrow()
+                    .get(imp.palette_set.get())
+                    .map(|s| s.name.clone())
+                    .unwrap_or_default();
+                self.choose_file(
+                    "Export Palette",
+                    action,
+                    &filter,
+                    Some(&format!("{name}.gpl")),
+                    Self::export_palette,
+                );
+            }
+            _ => self.choose_file(
+                "Import Palette",
+                action,
+                &filter,
+                None,
+                Self::import_palette,
+            ),
+        }
+    }
+
+    fn choose_file(
+        &self,
+        title: &str,
+        action: FileChooserAction,
+        filter: &FileFilter,
+        current_name: Option<&str>,
+        on_accept: fn(&Self, &Path) -> anyhow::Result<()>,
+    ) {
+        let imp = imp::AccentEditor::from_instance(&self);
+
+        let accept = match action {
+            FileChooserAction::Save => "Save",
+            _ => "Open",
+        };
+        let dialog = cascade! {
+            FileChooserNative::new(
+                Some(title),
+                self.root_window().as_ref(),
+                action,
+                Some(accept),
+                Some("Cancel"),
+            );
+            ..add_filter(filter);
+        };
+        if let Some(name) = current_name {
+            dialog.set_current_name(name);
+        }
+
+        dialog.connect_response(glib::clone!(@weak self as self_ => move |d, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = d.file().and_then(|f| f.path()) {
+                    if let Err(err) = on_accept(&self_, &path) {
+                        log::warn!("{:#}", &err);
+                        util::show_error(&self_, d.title().as_deref().unwrap_or_default(), &err);
+                    }
+                }
+            }
+            let imp = imp::AccentEditor::from_instance(&self_);
+            imp.file_chooser.replace(None);
+        }));
+        dialog.show();
+        // native dialogs aren't kept alive by GTK
+        imp.file_chooser.replace(Some(dialog));
+    }
+
+    fn choose_image(&self) {
+        let filter = cascade! {
+            FileFilter::new();
+            ..set_name(Some("Images"));
+            ..add_pixbuf_formats();
+        };
+        self.choose_file(
+            "Extract Accents from Image",
+            FileChooserAction::Open,
+            &filter,
+            None,
+            Self::extract_palette,
+        );
+    }
+
+    /// Shows the accents extracted from an image as a temporary palette and selects the best one
+    fn extract_palette(&self, path: &Path) -> anyhow::Result<()> {
+        let imp = imp::AccentEditor::from_instance(&self);
+        let algorithm = imp.contrast_algorithm.get();
+        let space = imp.working_space.get();
+
+        let candidates = util::extract_accents(path, EXTRACTED_ACCENTS)?;
+        let best = match candidates.first() {
+            Some(best) => best.color,
+            None => {
+                anyhow::bail!("The image doesn't have any colorful areas to pick an accent from")
+            }
+        };
+
+        let entries = |is_dark: bool| {
+            let base = imp.backgrounds.get().unwrap()[is_dark as usize].worst_case(is_dark);
+            candidates
+                .iter()
+                .map(|c| {
+                    AccentPalette::from_bg(
+                        format!("{:.0}% of the image", c.coverage * 100.0),
+                        c.color.into_color(),
+                        base,
+                        is_dark,
+                        algorithm,
+                        space,
+                    )
+                })
+                .collect()
+        };
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut palette_sets = palette_store::load_palettes();
+        // without an id, the palette is only saved when edited
+        palette_sets.push(PaletteSet {
+            id: String::new(),
+            name,
+            light: entries(false),
+            dark: entries(true),
+        });
+        self.set_palette_sets(palette_sets, "");
+
+        imp.use_palette_switch.get().unwrap().set_active(true);
+        imp.accent_button
+            .get()
+            .unwrap()
+            .set_rgba(&SRGB(best.into_color()).into());
+        Ok(())
+    }
+
+    fn import_palette(&self, path: &Path) -> anyhow::Result<()> {
+        let imp = imp::AccentEditor::from_instance(&self);
+
+        let mut palette_set = palette_store::import(
+            path,
+            imp.backgrounds.get().unwrap(),
+            imp.contrast_algorithm.get(),
+            imp.working_space.get(),
+        )?;
+        let saved_path = palette_store::save(&mut palette_set)?;
+        log::info!("Imported palette to {}", saved_path.display());
+        self.reload_palettes(&palette_set.id);
+        Ok(())
+    }
+
+    fn export_palette(&self, path: &Path) -> anyhow::Result<()> {
+        let imp = imp::AccentEditor::from_instance(&self);
+
+        let palette_sets = imp.palette_sets.borrow();
+        let palette_set = match palette_sets.get(imp.palette_set.get()) {
+            Some(palette_set) => palette_set,
+            None => return Ok(()),
+        };
+        palette_store::export(
+            path,
+            palette_set,
+            imp.style_manager.get().unwrap().is_dark(),
+        )
+        .with_context(|| format!("Failed to export palette to {}", path.display()))?;
+        log::info!("Exported palette to {}", path.display());
+        Ok(())
+    }
+
+    /// Reloads the palette sets after one was saved and selects it
+    fn reload_palettes(&self, selected_id: &str) {
+        self.set_palette_sets(palette_store::load_palettes(), selected_id);
+    }
+
+    fn set_palette_sets(&self, palette_sets: Vec<PaletteSet>, selected_id: &str) {
+        let imp = imp::AccentEditor::from_instance(&self);
+
+        let i = palette_sets
+            .iter()
+            .position(|s| s.id == selected_id)
+            .unwrap_or_default();
+        let palette_names: Vec<&str> = palette_sets.iter().map(|s| s.name.as_str()).collect();
+        let model = StringList::new(&palette_names);
+        imp.palette_sets.replace(palette_sets);
+
+        let palette_set_dropdown = imp.palette_set_dropdown.get().unwrap();
+        palette_set_dropdown.set_model(Some(&model));
+        palette_set_dropdown.set_selected(i as u32);
+        self.set_palette_set(i);
+    }
+
     fn connect_color_button(&self) {
         let imp = imp::AccentEditor::from_instance(&self);
 
@@ -222,6 +839,49 @@ impl AccentEditor {
         );
     }
 
+    fn set_contrast_algorithm(&self, algorithm: ContrastAlgorithm) {
+        let imp = imp::AccentEditor::from_instance(&self);
+        imp.contrast_algorithm.set(algorithm);
+        self.set_accent();
+    }
+
+    fn set_working_space(&self, space: WorkingSpace) {
+        let imp = imp::AccentEditor::from_instance(&self);
+        imp.working_space.set(space);
+        // match palettes in the same space
+        imp.metric_dropdown
+            .get()
+            .unwrap()
+            .set_selected(match space {
+                WorkingSpace::CieLch => 2,
+                WorkingSpace::Oklch => 3,
+            });
+        self.set_accent();
+    }
+
+    fn set_distance_metric(&self, metric: DistanceMetric) {
+        let imp = imp::AccentEditor::from_instance(&self);
+        imp.distance_metric.set(metric);
+        self.set_accent();
+    }
+
+    fn set_palette_set(&self, i: usize) {
+        let imp = imp::AccentEditor::from_instance(&self);
+        imp.palette_set.set(i);
+        self.set_palette_buttons(imp.style_manager.get().unwrap().is_dark());
+        self.set_accent();
+    }
+
+    /// the entries of the selected palette set for the color scheme
+    fn palette(&self, is_dark: bool) -> Vec<AccentPalette> {
+        let imp = imp::AccentEditor::from_instance(&self);
+        imp.palette_sets
+            .borrow()
+            .get(imp.palette_set.get())
+            .map(|set| set.palette(is_dark).to_vec())
+            .unwrap_or_default()
+    }
+
     fn set_accent(&self) {
         let imp = imp::AccentEditor::from_instance(&self);
         let palette_buttons = imp.palette_buttons.borrow();
@@ -232,114 +892,221 @@ impl AccentEditor {
         let accent_button = imp.accent_button.get().unwrap();
 
         let c = accent_button.rgba();
-        let mut lch_c = util::get_lch(c);
+        let lch_c = util::get_lch(c);
         let is_dark = style_manager.is_dark();
+        let algorithm = imp.contrast_algorithm.get();
+        let space = imp.working_space.get();
+        let backgrounds = *imp.backgrounds.get().unwrap();
+        imp.lch_sliders.get().unwrap().set_color(lch_c, space);
+        imp.accent_swatches
+            .get()
+            .unwrap()
+            .set_current(SRGB::from(c).into());
+        imp.tonal_scale
+            .get()
+            .unwrap()
+            .set_color(lch_c, algorithm, space);
 
-        if use_palette_switch.is_active() {
-            // find matching color from palette
-            let palette = if is_dark {
-                accent_palette::get_dark_palette(&self.style_context())
+        // the accent of a color scheme, with the palette entry it is from
+        let scheme_accent = |is_dark: bool| {
+            if use_palette_switch.is_active() {
+                // find matching color from palette
+                let palette = self.palette(is_dark);
+                let i = accent_palette::nearest(&palette, lch_c, imp.distance_metric.get())?;
+                Some((Accent::from(&palette[i]), Some(i)))
             } else {
-                accent_palette::get_light_palette(&self.style_context())
-            };
+                // derive colors automatically
+                let base = backgrounds[is_dark as usize].worst_case(is_dark);
+                let accent = Accent::derive(lch_c, base, is_dark, algorithm, space);
+                Some((accent, None))
+            }
+        };
+        let (accent, palette_entry) = match scheme_accent(is_dark) {
+            Some(accent) => accent,
+            None => return,
+        };
+        if let Some(i) = palette_entry {
+            palette_buttons[i].set_active(true);
+        }
 
-            let (i, _) =
-                palette
-                    .iter()
-                    .enumerate()
-                    .fold((0, f32::MAX), |(pre_i, pre_d), (i, cur_p)| {
-                        let c_comp: Lch = SRGB::from(cur_p.accent_color_bg).0.into_color();
-                        let dh = f32::min(
-                            c_comp.hue.to_degrees() - lch_c.hue.to_degrees(),
-                            360.0 - c_comp.hue.to_degrees() - lch_c.hue.to_degrees(),
-                        )
-                        .abs();
-                        let dc = (c_comp.chroma - lch_c.chroma).abs();
-                        let cur_d = if c_comp.chroma < 20.0 {
-                            dc.powi(2) + dh
-                        } else {
-                            dc + dh.powi(2)
-                        };
-                        if pre_d < cur_d {
-                            (pre_i, pre_d)
-                        } else {
-                            (i, cur_d)
-                        }
-                    });
-            let b = &palette_buttons[i];
-            b.set_active(true);
-            let p = &palette[i];
-            let accent_as_fg: SRGB = SRGB::from(p.accent_color);
-            let accent_bg: SRGB = SRGB::from(p.accent_color);
-            let fg = SRGB::from(p.accent_color_fg);
-            let mut style = css_provider.to_str().to_string();
-            style += &format!(
-                r#"
-            @define-color accent_color #{};
-            @define-color accent_bg_color #{};
-            @define-color accent_fg_color #{};
-            "#,
-                util::hex_from_rgba(accent_as_fg.into()),
-                util::hex_from_rgba(accent_bg.into()),
-                util::hex_from_rgba(fg.into())
-            );
-            css_provider.load_from_data(style.as_bytes());
+        // both color schemes are checked, so an accent works after switching
+        let other = scheme_accent(!is_dark).map(|(accent, _)| accent);
+        let accents = if is_dark {
+            [other, Some(accent)]
         } else {
-            // derive colors automatically
-
-            // TODO find a good way to use the worst case for base lighness & contrast when deriving colors
-            // it is currently a bit buggy bc the new colors aren't available right away after color scheme change
-            // currently the base is assumed to be black or white instead...
-
-            let sc = &self.style_context();
-            // let view_bg_l = util::get_lch(sc.lookup_color("view_bg_color").unwrap()).l;
-            // let window_bg_l = util::get_lch(sc.lookup_color("window_bg_color").unwrap()).l;
-            (lch_c.l) = if is_dark {
-                Lch::<D65>::min_l()
-                // [view_bg_l, window_bg_l]
-                //     .into_iter()
-                //     .reduce(f32::min)
-                //     .unwrap()
-            } else {
-                Lch::<D65>::max_l()
-                // [view_bg_l, window_bg_l]
-                //     .into_iter()
-                //     .reduce(f32::max)
-                //     .unwrap()
-            };
-            let (derived_fg, fg_contrast, bg_contrast) = if is_dark {
-                (SRGB(Srgb::new(1.0, 1.0, 1.0)), 12.0, 3.0)
-            } else {
-                (SRGB(Srgb::new(0.0, 0.0, 0.0)), 7.0, 1.3) // 7.0 & 1.1 are minimum required
+            [Some(accent), other]
+        };
+        imp.contrast_report
+            .get()
+            .unwrap()
+            .set_accents(accents, backgrounds, algorithm);
+        imp.color_readout.get().unwrap().set_accent(&accent);
+
+        let style = imp.base_css.get().unwrap().clone() + &accent.to_css();
+        css_provider.load_from_data(style.as_bytes());
+        imp.accent.replace(Some(accent));
+
+        if !imp.restoring.get() {
+            // selecting a palette entry sets the color button, so it is read again
+            let state = EditorState {
+                color: SRGB::from(accent_button.rgba()).into(),
+                use_palette: use_palette_switch.is_active(),
+                palette_entry,
+                color_scheme: style_manager.color_scheme(),
             };
-            let derived_accent_as_fg: SRGB =
-                if let Ok(fg_c) = util::derive_color(lch_c, Some(fg_contrast), None) {
-                    SRGB(fg_c.into_color())
-                } else {
-                    eprintln!("Failed to derive color with requested contrast");
-                    derived_fg
-                };
-            let derived_bg: SRGB = SRGB(
-                util::derive_color(lch_c, Some(bg_contrast), None)
-                    .unwrap_or_else(|e| {
-                        dbg!(e);
-                        lch_c
-                    })
-                    .into_color(),
-            );
+            imp.history
+                .borrow_mut()
+                .record(state, imp.edit_source.get(), Instant::now());
+            self.update_history_actions();
+        }
+    }
 
-            let mut style = css_provider.to_str().to_string();
-            style += &format!(
-                r#"
-            @define-color accent_color #{};
-            @define-color accent_bg_color #{};
-            @define-color accent_fg_color #{};
-            "#,
-                util::hex_from_rgba(derived_accent_as_fg.into()),
-                util::hex_from_rgba(derived_bg.into()),
-                util::hex_from_rgba(derived_fg.into())
+    /// Restores the editor to `state` from the history
+    fn restore(&self, state: EditorState) {
+        let imp = imp::AccentEditor::from_instance(&self);
+        imp.restoring.set(true);
+
+        imp.use_palette_switch
+            .get()
+            .unwrap()
+            .set_active(state.use_palette);
+        let button = match state.color_scheme {
+            ColorScheme::Default => Some(0),
+            ColorScheme::ForceLight => Some(1),
+            ColorScheme::ForceDark => Some(2),
+            _ => None,
+        };
+        match (imp.color_scheme_buttons.get(), button) {
+            (Some(buttons), Some(i)) => buttons[i].set_active(true),
+            _ => imp
+                .style_manager
+                .get()
+                .unwrap()
+                .set_color_scheme(state.color_scheme),
+        }
+        imp.accent_button
+            .get()
+            .unwrap()
+            .set_rgba(&SRGB(state.color).into());
+        let palette_button = state
+            .palette_entry
+            .and_then(|i| imp.palette_buttons.borrow().get(i).cloned());
+        if let Some(b) = palette_button {
+            b.set_active(true);
+        }
+
+        imp.restoring.set(false);
+        self.update_history_actions();
+    }
+
+    fn update_history_actions(&self) {
+        let imp = imp::AccentEditor::from_instance(&self);
+        let history = imp.history.borrow();
+        if let (Some(undo), Some(redo)) = (imp.undo_action.get(), imp.redo_action.get()) {
+            undo.set_enabled(history.can_undo());
+            redo.set_enabled(history.can_redo());
+        }
+    }
+
+    fn set_follow_wallpaper(&self, follow: bool) {
+        let imp = imp::AccentEditor::from_instance(&self);
+        let settings = match imp.wallpaper_settings.get() {
+            Some(settings) => settings,
+            None => return,
+        };
+
+        if let Some(handler) = imp.wallpaper_handler.take() {
+            settings.disconnect(handler);
+        }
+        if follow {
+            let handler = wallpaper::connect_wallpaper_changed(
+                settings,
+                glib::clone!(@weak self as self_ => move |_| {
+                    self_.follow_wallpaper();
+                }),
             );
-            css_provider.load_from_data(style.as_bytes());
+            imp.wallpaper_handler.replace(Some(handler));
+            self.follow_wallpaper();
+        }
+    }
+
+    /// Sets the accent color to the best accent of the wallpaper,
+    /// which is matched to the palette when it is used
+    fn follow_wallpaper(&self) {
+        let imp = imp::AccentEditor::from_instance(&self);
+        let settings = match imp.wallpaper_settings.get() {
+            Some(settings) => settings,
+            None => return,
+        };
+        let is_dark = imp.style_manager.get().unwrap().is_dark();
+        let request = imp.wallpaper_request.get() + 1;
+        imp.wallpaper_request.set(request);
+
+        wallpaper::wallpaper_accent_async(
+            settings,
+            is_dark,
+            glib::clone!(@weak self as self_ => move |res| {
+                let imp = imp::AccentEditor::from_instance(&self_);
+                // the wallpaper changed again while it was decoded
+                if imp.wallpaper_request.get() != request {
+                    return;
+                }
+                match res {
+                    Ok(Some(c)) => imp
+                        .accent_button
+                        .get()
+                        .unwrap()
+                        .set_rgba(&SRGB(c.into_color()).into()),
+                    Ok(None) => log::info!(
+                        "The wallpaper doesn't have any colorful areas to pick an accent from"
+                    ),
+                    Err(err) => log::warn!("Failed to derive accent from wallpaper, {}", &err),
+                }
+            }),
+        );
+    }
+
+    fn apply(&self) {
+        let imp = imp::AccentEditor::from_instance(&self);
+
+        let accent = match imp.accent.borrow().as_ref() {
+            Some(accent) => *accent,
+            None => return,
+        };
+        let mut results = vec![gtk_css::apply_gtk4(&accent)];
+        if imp.gtk3_switch.get().unwrap().is_active() {
+            results.push(gtk_css::apply_gtk3(&accent));
+        }
+
+        let mut applied = false;
+        for res in results {
+            match res {
+                Ok(path) => {
+                    log::info!("Applied accent to {}", path.display());
+                    applied = true;
+                }
+                Err(err) => log::warn!("Failed to apply accent, {}", &err),
+            }
+        }
+
+        // only accents that were written are recent
+        if applied {
+            let source = imp.accent_button.get().unwrap().rgba();
+            imp.accent_swatches
+                .get()
+                .unwrap()
+                .add_recent(SRGB::from(source).into());
+        }
+    }
+
+    fn remove(&self) {
+        // always clean up both files, the GTK 3 switch may have changed since applying
+        for res in [gtk_css::remove_gtk4(), gtk_css::remove_gtk3()] {
+            match res {
+                Ok(Some(path)) => log::info!("Removed accent from {}", path.display()),
+                Ok(None) => {}
+                Err(err) => log::warn!("Failed to remove accent, {}", &err),
+            }
         }
     }
 
@@ -362,23 +1129,34 @@ impl AccentEditor {
             i += 1;
         }
 
-        let palette = if is_dark {
-            accent_palette::get_dark_palette(&self.style_context())
-        } else {
-            accent_palette::get_light_palette(&self.style_context())
-        };
+        let palette = self.palette(is_dark);
+
+        // color the buttons with the background of their accent
+        let mut style = String::new();
+        for (i, c) in palette.iter().enumerate() {
+            style += &format!(
+                "#palette-{} {{ background-color: #{}; }}\n",
+                i,
+                color::hex_from_srgb(c.accent_color_bg)
+            );
+        }
+        imp.palette_css_provider
+            .get()
+            .unwrap()
+            .load_from_data(style.as_bytes());
 
         for (i, c) in palette.iter().cloned().enumerate() {
             let active = active.unwrap_or_else(|| palette.len()) == i;
             view! {
                 button = &ToggleButton {
                     add_css_class: "opaque",
-                    set_widget_name: &c.name,
+                    set_widget_name: &format!("palette-{}", i),
+                    set_tooltip_text: Some(&c.name),
                     set_group: palette_toggles.get(0),
                     set_active: active,
                     connect_toggled: glib::clone!(@weak self as self_, @weak imp.accent_button as accent_button => move |b| {
                         if b.is_active() {
-                            accent_button.get().unwrap().set_rgba(&c.accent_color_bg);
+                            accent_button.get().unwrap().set_rgba(&SRGB(c.accent_color_bg).into());
                             self_.set_accent();
                         }
                     })
@@ -388,7 +1166,7 @@ impl AccentEditor {
                 imp.accent_button
                     .get()
                     .unwrap()
-                    .set_rgba(&c.accent_color_bg);
+                    .set_rgba(&SRGB(c.accent_color_bg).into());
             }
             palette_box.append(&button);
             palette_toggles.push(button);
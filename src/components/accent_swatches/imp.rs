@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{gio, glib, subclass::prelude::*, Box, CssProvider};
+use once_cell::sync::OnceCell;
+use palette::Srgb;
+use std::{cell::Cell, rc::Rc};
+
+// Object holding the state
+#[derive(Default)]
+pub struct AccentSwatches {
+    pub css_provider: Rc<OnceCell<CssProvider>>,
+    pub settings: Rc<OnceCell<gio::Settings>>,
+    pub favorites_box: Rc<OnceCell<Box>>,
+    pub recent_box: Rc<OnceCell<Box>>,
+    /// the source color that is pinned
+    pub current: Rc<Cell<Option<Srgb>>>,
+    pub on_select: Rc<OnceCell<std::boxed::Box<dyn Fn(Srgb)>>>,
+}
+
+// The central trait for subclassing a GObject
+#[glib::object_subclass]
+impl ObjectSubclass for AccentSwatches {
+    const NAME: &'static str = "AccentSwatchesWidget";
+    type Type = super::AccentSwatches;
+    type ParentType = Box;
+}
+
+// Trait shared by all GObjects
+impl ObjectImpl for AccentSwatches {}
+
+// Trait shared by all widgets
+impl WidgetImpl for AccentSwatches {}
+
+// Trait shared by all boxes
+impl BoxImpl for AccentSwatches {}
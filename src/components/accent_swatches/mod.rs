@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{
+    self, gdk, glib, subclass::prelude::*, Align, Box, Button, CssProvider, Label, Orientation,
+    StyleContext,
+};
+use adw::prelude::*;
+use cascade::cascade;
+use hue_chroma_accent_core::color;
+use palette::Srgb;
+use relm4_macros::view;
+
+use crate::saved_accents::{self, FAVORITE_ACCENTS, RECENT_ACCENTS};
+mod imp;
+
+glib::wrapper! {
+    pub struct AccentSwatches(ObjectSubclass<imp::AccentSwatches>)
+        @extends gtk::Box, gtk::Widget,
+    @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl AccentSwatches {
+    /// `on_select` gets the source color of a clicked swatch
+    pub fn new<F: Fn(Srgb) + 'static>(on_select: F) -> Self {
+        let self_: Self = glib::Object::new(&[]).expect("Failed to create Accent Swatches Widget");
+
+        let imp = imp::AccentSwatches::from_instance(&self_);
+
+        cascade! {
+            &self_;
+            ..set_orientation(Orientation::Vertical);
+            ..set_spacing(4);
+            ..set_margin_top(4);
+            ..set_margin_bottom(4);
+            ..set_margin_start(4);
+            ..set_margin_end(4);
+        };
+
+        let css_provider = CssProvider::new();
+        StyleContext::add_provider_for_display(
+            &gdk::Display::default().expect("Error initializing GTK CSS provider."),
+            &css_provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+
+        view! {
+            favorites_row = Box {
+                set_orientation: Orientation::Horizontal,
+                set_spacing: 4,
+                set_hexpand: true,
+
+                append: favorites_label = &Label {
+                    set_text: "Favorites",
+                    set_width_chars: 9,
+                    set_xalign: 0.0,
+                },
+                append: favorites_box = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 4,
+                    set_hexpand: true,
+                },
+                append: pin_button = &Button {
+                    set_icon_name: "starred-symbolic",
+                    set_tooltip_text: Some("Pin the Current Color"),
+                    set_halign: Align::End,
+                },
+            }
+        };
+        view! {
+            recent_row = Box {
+                set_orientation: Orientation::Horizontal,
+                set_spacing: 4,
+                set_hexpand: true,
+
+                append: recent_label = &Label {
+                    set_text: "Recent",
+                    set_width_chars: 9,
+                    set_xalign: 0.0,
+                },
+                append: recent_box = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 4,
+                    set_hexpand: true,
+                },
+            }
+        };
+        self_.append(&favorites_row);
+        self_.append(&recent_row);
+
+        pin_button.connect_clicked(glib::clone!(@weak self_ => move |_| {
+            self_.pin();
+        }));
+
+        imp.css_provider.set(css_provider).unwrap();
+        imp.favorites_box.set(favorites_box).unwrap();
+        imp.recent_box.set(recent_box).unwrap();
+        imp.on_select
+            .set(std::boxed::Box::new(on_select))
+            .unwrap_or_else(|_| unreachable!());
+
+        match saved_accents::app_settings() {
+            Some(settings) => {
+                // also follows changes made by other instances
+                settings.connect_changed(
+                    None,
+                    glib::clone!(@weak self_ => move |_, key| {
+                        if key == RECENT_ACCENTS || key == FAVORITE_ACCENTS {
+                            self_.set_swatches();
+                        }
+                    }),
+                );
+                imp.settings.set(settings).unwrap();
+                self_.set_swatches();
+            }
+            None => {
+                log::warn!("Failed to load the settings, recent & favorite accents are not saved");
+                self_.set_visible(false);
+            }
+        }
+
+        self_
+    }
+
+    /// Sets the source color that is pinned as a favorite
+    pub fn set_current(&self, c: Srgb) {
+        let imp = imp::AccentSwatches::from_instance(&self);
+        imp.current.set(Some(c));
+    }
+
+    pub fn add_recent(&self, c: Srgb) {
+        let imp = imp::AccentSwatches::from_instance(&self);
+        if let Some(settings) = imp.settings.get() {
+            if let Err(err) = saved_accents::add_recent(settings, c) {
+                log::warn!("Failed to save recent accent, {}", &err);
+            }
+        }
+    }
+
+    fn pin(&self) {
+        let imp = imp::AccentSwatches::from_instance(&self);
+        if let (Some(settings), Some(c)) = (imp.settings.get(), imp.current.get()) {
+            if let Err(err) = saved_accents::add_favorite(settings, c) {
+                log::warn!("Failed to save favorite accent, {}", &err);
+            }
+        }
+    }
+
+    fn unpin(&self, c: Srgb) {
+        let imp = imp::AccentSwatches::from_instance(&self);
+        if let Some(settings) = imp.settings.get() {
+            if let Err(err) = saved_accents::remove_favorite(settings, c) {
+                log::warn!("Failed to remove favorite accent, {}", &err);
+            }
+        }
+    }
+
+    fn set_swatches(&self) {
+        let imp = imp::AccentSwatches::from_instance(&self);
+        let settings = match imp.settings.get() {
+            Some(settings) => settings,
+            None => return,
+        };
+        let favorites = saved_accents::accents(settings, FAVORITE_ACCENTS);
+        let recent = saved_accents::accents(settings, RECENT_ACCENTS);
+
+        let mut style = String::new();
+        for (kind, accents) in [("favorite", &favorites), ("recent", &recent)] {
+            for (i, c) in accents.iter().enumerate() {
+                style += &format!(
+                    "#{kind}-{i} {{ background-color: #{}; }}\n",
+                    color::hex_from_srgb(*c)
+                );
+            }
+        }
+        imp.css_provider
+            .get()
+            .unwrap()
+            .load_from_data(style.as_bytes());
+
+        let favorites_box = imp.favorites_box.get().unwrap();
+        while let Some(c) = favorites_box.first_child() {
+            favorites_box.remove(&c);
+        }
+        for (i, c) in favorites.into_iter().enumerate() {
+            let swatch = self.swatch(&format!("favorite-{i}"), c);
+            let unpin_button = cascade! {
+                Button::new();
+                ..set_icon_name("window-close-symbolic");
+                ..add_css_class("flat");
+                ..add_css_class("circular");
+                ..set_valign(Align::Center);
+                ..set_tooltip_text(Some("Remove from Favorites"));
+            };
+            unpin_button.connect_clicked(glib::clone!(@weak self as self_ => move |_| {
+                self_.unpin(c);
+            }));
+            view! {
+                favorite = Box {
+                    set_orientation: Orientation::Horizontal,
+
+                    append: &swatch,
+                    append: &unpin_button,
+                }
+            };
+            favorites_box.append(&favorite);
+        }
+
+        let recent_box = imp.recent_box.get().unwrap();
+        while let Some(c) = recent_box.first_child() {
+            recent_box.remove(&c);
+        }
+        for (i, c) in recent.into_iter().enumerate() {
+            recent_box.append(&self.swatch(&format!("recent-{i}"), c));
+        }
+    }
+
+    /// a button colored with `c` that selects it
+    fn swatch(&self, name: &str, c: Srgb) -> Button {
+        let button = cascade! {
+            Button::new();
+            ..add_css_class("opaque");
+            ..set_widget_name(name);
+            ..set_tooltip_text(Some(&format!("#{}", color::hex_from_srgb(c))));
+        };
+        button.connect_clicked(glib::clone!(@weak self as self_ => move |_| {
+            let imp = imp::AccentSwatches::from_instance(&self_);
+            (imp.on_select.get().unwrap())(c);
+        }));
+        button
+    }
+}
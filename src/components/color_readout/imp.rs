@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{glib, subclass::prelude::*, Box, Button};
+use hue_chroma_accent_core::color::Accent;
+use once_cell::sync::OnceCell;
+use std::{cell::Cell, rc::Rc};
+
+// Object holding the state
+#[derive(Default)]
+pub struct ColorReadout {
+    /// a button for each format & role, copying its label
+    pub cells: Rc<OnceCell<Vec<Vec<Button>>>>,
+    pub accent: Rc<Cell<Option<Accent>>>,
+}
+
+// The central trait for subclassing a GObject
+#[glib::object_subclass]
+impl ObjectSubclass for ColorReadout {
+    const NAME: &'static str = "ColorReadoutWidget";
+    type Type = super::ColorReadout;
+    type ParentType = Box;
+}
+
+// Trait shared by all GObjects
+impl ObjectImpl for ColorReadout {}
+
+// Trait shared by all widgets
+impl WidgetImpl for ColorReadout {}
+
+// Trait shared by all boxes
+impl BoxImpl for ColorReadout {}
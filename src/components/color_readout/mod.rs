@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{self, glib, subclass::prelude::*, Align, Box, Button, Grid, Label, Orientation};
+use adw::prelude::*;
+use cascade::cascade;
+use hue_chroma_accent_core::{
+    color::{self, Accent},
+    css_color::{self, Notation},
+};
+use palette::Srgb;
+use relm4_macros::view;
+
+mod imp;
+
+glib::wrapper! {
+    pub struct ColorReadout(ObjectSubclass<imp::ColorReadout>)
+        @extends gtk::Box, gtk::Widget,
+    @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl Default for ColorReadout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// the number of rows, one for each notation & one for the `@define-color` rules
+const ROWS: usize = Notation::ALL.len() + 1;
+
+/// `c` in row `row`, the last row is the `@define-color` rule named `name`
+fn cell_text(row: usize, name: &str, c: Srgb) -> String {
+    match Notation::ALL.get(row) {
+        Some(notation) => css_color::to_css(c, *notation),
+        None => color::define_color(name, c),
+    }
+}
+
+impl ColorReadout {
+    pub fn new() -> Self {
+        let self_: Self = glib::Object::new(&[]).expect("Failed to create Color Readout Widget");
+
+        let imp = imp::ColorReadout::from_instance(&self_);
+
+        cascade! {
+            &self_;
+            ..set_orientation(Orientation::Vertical);
+            ..set_spacing(4);
+            ..set_margin_top(4);
+            ..set_margin_bottom(4);
+            ..set_margin_start(4);
+            ..set_margin_end(4);
+        };
+
+        view! {
+            header_box = Box {
+                set_orientation: Orientation::Horizontal,
+                set_spacing: 4,
+                set_hexpand: true,
+
+                append: title = &Label {
+                    set_text: "Colors",
+                    set_halign: Align::Start,
+                    set_hexpand: true,
+                    add_css_class: "heading",
+                },
+                append: copy_css_button = &Button {
+                    set_label: "Copy CSS",
+                    set_tooltip_text: Some("Copy the @define-color rules"),
+                },
+            }
+        };
+        let grid = cascade! {
+            Grid::new();
+            ..set_column_spacing(4);
+            ..set_row_spacing(2);
+            ..set_hexpand(true);
+        };
+        self_.append(&header_box);
+        self_.append(&grid);
+
+        for (column, (name, _)) in Accent::default().roles().iter().enumerate() {
+            let label = cascade! {
+                Label::new(Some(name));
+                ..set_xalign(0.0);
+                ..add_css_class("caption-heading");
+            };
+            grid.attach(&label, column as i32 + 1, 0, 1, 1);
+        }
+
+        let cells = (0..ROWS)
+            .map(|row| {
+                let name = Notation::ALL.get(row).map(|n| n.name()).unwrap_or("GTK");
+                let label = cascade! {
+                    Label::new(Some(name));
+                    ..set_xalign(0.0);
+                    ..add_css_class("dim-label");
+                };
+                grid.attach(&label, 0, row as i32 + 1, 1, 1);
+
+                (0..3)
+                    .map(|column| {
+                        let button = cascade! {
+                            Button::new();
+                            ..add_css_class("flat");
+                            ..add_css_class("monospace");
+                            ..set_tooltip_text(Some("Copy"));
+                            ..set_hexpand(true);
+                        };
+                        button.connect_clicked(|b| {
+                            if let Some(text) = b.label() {
+                                b.clipboard().set_text(&text);
+                            }
+                        });
+                        grid.attach(&button, column + 1, row as i32 + 1, 1, 1);
+                        button
+                    })
+                    .collect()
+            })
+            .collect();
+
+        copy_css_button.connect_clicked(glib::clone!(@weak self_ => move |b| {
+            let imp = imp::ColorReadout::from_instance(&self_);
+            if let Some(accent) = imp.accent.get() {
+                b.clipboard().set_text(accent.to_css().trim());
+            }
+        }));
+
+        imp.cells.set(cells).unwrap();
+
+        self_
+    }
+
+    /// Shows the roles of `accent` in every format
+    pub fn set_accent(&self, accent: &Accent) {
+        let imp = imp::ColorReadout::from_instance(&self);
+        for (row, buttons) in imp.cells.get().unwrap().iter().enumerate() {
+            for (button, (name, c)) in buttons.iter().zip(accent.roles()) {
+                button.set_label(&cell_text(row, name, c));
+            }
+        }
+        imp.accent.set(Some(*accent));
+    }
+}
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{glib, subclass::prelude::*, Box, Label};
+use once_cell::sync::OnceCell;
+use std::rc::Rc;
+
+/// The labels of a checked pair of colors in one color scheme
+pub struct ReportCell {
+    pub value: Label,
+    pub badge: Label,
+}
+
+// Object holding the state
+#[derive(Default)]
+pub struct ContrastReport {
+    /// the light & dark cells of each check
+    pub rows: Rc<OnceCell<Vec<[ReportCell; 2]>>>,
+}
+
+// The central trait for subclassing a GObject
+#[glib::object_subclass]
+impl ObjectSubclass for ContrastReport {
+    const NAME: &'static str = "ContrastReportWidget";
+    type Type = super::ContrastReport;
+    type ParentType = Box;
+}
+
+// Trait shared by all GObjects
+impl ObjectImpl for ContrastReport {}
+
+// Trait shared by all widgets
+impl WidgetImpl for ContrastReport {}
+
+// Trait shared by all boxes
+impl BoxImpl for ContrastReport {}
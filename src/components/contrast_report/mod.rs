@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{self, glib, subclass::prelude::*, Align, Grid, Label, Orientation};
+use adw::prelude::*;
+use cascade::cascade;
+use hue_chroma_accent_core::color::{Accent, Backgrounds, ContrastAlgorithm, ContrastLevel};
+use palette::{IntoColor, Lch};
+
+mod imp;
+
+/// How a pair of colors is checked
+#[derive(Copy, Clone, PartialEq)]
+enum Check {
+    Text,
+    NonText,
+    /// the contrast the accent background is derived for, which isn't a conformance level
+    DerivationTarget,
+}
+
+/// The checked pairs of colors
+const CHECKS: [(&str, Check); 5] = [
+    ("Accent on window", Check::Text),
+    ("Accent on view", Check::Text),
+    ("Text on accent", Check::Text),
+    ("Accent background on window", Check::NonText),
+    ("Accent background target", Check::DerivationTarget),
+];
+
+glib::wrapper! {
+    pub struct ContrastReport(ObjectSubclass<imp::ContrastReport>)
+        @extends gtk::Box, gtk::Widget,
+    @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl Default for ContrastReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContrastReport {
+    pub fn new() -> Self {
+        let self_: Self = glib::Object::new(&[]).expect("Failed to create Contrast Report Widget");
+
+        let imp = imp::ContrastReport::from_instance(&self_);
+
+        cascade! {
+            &self_;
+            ..set_orientation(Orientation::Vertical);
+            ..set_spacing(4);
+            ..set_margin_top(4);
+            ..set_margin_bottom(4);
+            ..set_margin_start(4);
+            ..set_margin_end(4);
+        };
+
+        let title = cascade! {
+            Label::new(Some("Contrast"));
+            ..set_halign(Align::Start);
+            ..add_css_class("heading");
+        };
+        let grid = cascade! {
+            Grid::new();
+            ..set_column_spacing(8);
+            ..set_row_spacing(4);
+            ..set_hexpand(true);
+        };
+        self_.append(&title);
+        self_.append(&grid);
+
+        for (i, scheme) in ["Light", "Dark"].into_iter().enumerate() {
+            let label = cascade! {
+                Label::new(Some(scheme));
+                ..set_xalign(0.0);
+                ..add_css_class("caption-heading");
+            };
+            grid.attach(&label, 2 * i as i32 + 1, 0, 2, 1);
+        }
+
+        let rows = CHECKS
+            .iter()
+            .enumerate()
+            .map(|(row, (name, _))| {
+                let row = row as i32 + 1;
+                let name_label = cascade! {
+                    Label::new(Some(name));
+                    ..set_halign(Align::Start);
+                    ..set_hexpand(true);
+                };
+                grid.attach(&name_label, 0, row, 1, 1);
+
+                [0, 1].map(|i| {
+                    let value = cascade! {
+                        Label::new(None);
+                        ..add_css_class("numeric");
+                    };
+                    let badge = cascade! {
+                        Label::new(None);
+                        ..add_css_class("contrast-badge");
+                        ..add_css_class("caption-heading");
+                    };
+                    grid.attach(&value, 2 * i + 1, row, 1, 1);
+                    grid.attach(&badge, 2 * i + 2, row, 1, 1);
+                    imp::ReportCell { value, badge }
+                })
+            })
+            .collect();
+
+        imp.rows.set(rows).unwrap_or_else(|_| unreachable!());
+
+        self_
+    }
+
+    /// Checks the contrasts of the accents of the light & dark color scheme against their backgrounds
+    /// `None` if there is no accent for the color scheme, e.g. the palette doesn't have one
+    pub fn set_accents(
+        &self,
+        accents: [Option<Accent>; 2],
+        backgrounds: [Backgrounds; 2],
+        algorithm: ContrastAlgorithm,
+    ) {
+        let imp = imp::ContrastReport::from_instance(&self);
+        let rows = imp.rows.get().unwrap();
+
+        for (is_dark, (accent, backgrounds)) in [false, true]
+            .into_iter()
+            .zip(accents.iter().zip(backgrounds))
+        {
+            let cells = rows.iter().map(|row| &row[is_dark as usize]);
+            let accent = match accent {
+                Some(accent) => accent,
+                None => {
+                    for cell in cells {
+                        cell.value.set_text("–");
+                        cell.badge.set_text("");
+                        cell.badge.set_tooltip_text(None);
+                    }
+                    continue;
+                }
+            };
+
+            let accent_as_fg: Lch = accent.accent.into_color();
+            let accent_bg: Lch = accent.accent_bg.into_color();
+            let accent_fg: Lch = accent.accent_fg.into_color();
+            let pairs = [
+                (accent_as_fg, backgrounds.window_bg),
+                (accent_as_fg, backgrounds.view_bg),
+                (accent_fg, accent_bg),
+                (accent_bg, backgrounds.window_bg),
+                (accent_bg, backgrounds.window_bg),
+            ];
+
+            for ((cell, (fg, bg)), (_, check)) in cells.zip(pairs).zip(CHECKS) {
+                let contrast = algorithm.contrast(fg, bg);
+                let (text, class, tooltip) = match check {
+                    Check::Text | Check::NonText => {
+                        let level = if check == Check::Text {
+                            algorithm.text_level(contrast)
+                        } else {
+                            algorithm.non_text_level(contrast)
+                        };
+                        let class = match level {
+                            ContrastLevel::Aaa => "success",
+                            // non-text elements only have AA
+                            ContrastLevel::Aa if check == Check::NonText => "success",
+                            ContrastLevel::Aa => "warning",
+                            ContrastLevel::Fail => "error",
+                        };
+                        (level.to_string(), class, None)
+                    }
+                    Check::DerivationTarget => {
+                        let (_, target) = algorithm.accent_contrast(is_dark);
+                        let tooltip = format!(
+                            "Accent backgrounds are derived for at least {}",
+                            algorithm.format(target)
+                        );
+                        let text = if algorithm.meets_accent_bg(contrast, is_dark) {
+                            "Met"
+                        } else {
+                            "Missed"
+                        };
+                        (text.to_string(), "dim-label", Some(tooltip))
+                    }
+                };
+                cell.value.set_text(&algorithm.format(contrast));
+                cell.badge.set_text(&text);
+                cell.badge.set_tooltip_text(tooltip.as_deref());
+                for class in ["success", "warning", "error", "dim-label"] {
+                    cell.badge.remove_css_class(class);
+                }
+                cell.badge.add_css_class(class);
+            }
+        }
+    }
+}
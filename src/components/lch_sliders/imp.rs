@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{glib, subclass::prelude::*, Box, CssProvider, Scale};
+use hue_chroma_accent_core::color::WorkingSpace;
+use once_cell::sync::OnceCell;
+use palette::Lch;
+use std::{cell::Cell, rc::Rc};
+
+// Object holding the state
+#[derive(Default)]
+pub struct LchSliders {
+    pub css_provider: Rc<OnceCell<CssProvider>>,
+    /// one slider for each of `LchChannel::ALL`
+    pub scales: Rc<OnceCell<Vec<Scale>>>,
+    /// lightness, chroma & hue of the sliders in the working space
+    pub components: Rc<Cell<(f32, f32, f32)>>,
+    pub working_space: Rc<Cell<WorkingSpace>>,
+    /// set while the sliders follow a color, so they don't pass it back
+    pub updating: Rc<Cell<bool>>,
+    pub on_change: Rc<OnceCell<std::boxed::Box<dyn Fn(Lch)>>>,
+}
+
+// The central trait for subclassing a GObject
+#[glib::object_subclass]
+impl ObjectSubclass for LchSliders {
+    const NAME: &'static str = "LchSlidersWidget";
+    type Type = super::LchSliders;
+    type ParentType = Box;
+}
+
+// Trait shared by all GObjects
+impl ObjectImpl for LchSliders {}
+
+// Trait shared by all widgets
+impl WidgetImpl for LchSliders {}
+
+// Trait shared by all boxes
+impl BoxImpl for LchSliders {}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{
+    self, gdk, glib, subclass::prelude::*, Align, Box, CssProvider, Label, Orientation,
+    PositionType, Scale, StyleContext,
+};
+use adw::prelude::*;
+use cascade::cascade;
+use hue_chroma_accent_core::color::{self, LchChannel, WorkingSpace};
+use palette::{IntoColor, Lch};
+use relm4_macros::view;
+mod imp;
+
+/// number of color stops in each slider track
+const GRADIENT_STOPS: usize = 25;
+/// below this chroma the hue of a color is meaningless, so the hue slider keeps its value
+const NEUTRAL_CHROMA: f32 = 0.5;
+
+glib::wrapper! {
+    pub struct LchSliders(ObjectSubclass<imp::LchSliders>)
+        @extends gtk::Box, gtk::Widget,
+    @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+/// the label & widget name of the slider of `channel`
+fn channel_name(channel: LchChannel) -> (&'static str, &'static str) {
+    match channel {
+        LchChannel::Lightness => ("Lightness", "lch-lightness"),
+        LchChannel::Chroma => ("Chroma", "lch-chroma"),
+        LchChannel::Hue => ("Hue", "lch-hue"),
+    }
+}
+
+impl LchSliders {
+    /// `on_change` gets the color of the sliders, mapped into sRGB, whenever one of them is moved
+    pub fn new<F: Fn(Lch) + 'static>(on_change: F) -> Self {
+        let self_: Self = glib::Object::new(&[]).expect("Failed to create Lch Sliders Widget");
+
+        let imp = imp::LchSliders::from_instance(&self_);
+
+        cascade! {
+            &self_;
+            ..set_orientation(Orientation::Vertical);
+            ..set_spacing(4);
+            ..set_margin_top(4);
+            ..set_margin_bottom(4);
+            ..set_margin_start(4);
+            ..set_margin_end(4);
+        };
+
+        let css_provider = CssProvider::new();
+        StyleContext::add_provider_for_display(
+            &gdk::Display::default().expect("Error initializing GTK CSS provider."),
+            &css_provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+
+        let scales = LchChannel::ALL
+            .iter()
+            .map(|&channel| {
+                let (label, name) = channel_name(channel);
+                let (min, max) = channel.range();
+                let scale = cascade! {
+                    Scale::with_range(Orientation::Horizontal, min.into(), max.into(), 0.5);
+                    ..set_widget_name(name);
+                    ..add_css_class("lch-slider");
+                    ..set_hexpand(true);
+                    ..set_has_origin(false);
+                    ..set_draw_value(true);
+                    ..set_value_pos(PositionType::Right);
+                    ..set_digits(1);
+                };
+                scale.connect_value_changed(glib::clone!(@weak self_ => move |s| {
+                    self_.on_value_changed(channel, s.value() as f32);
+                }));
+
+                view! {
+                    row = Box {
+                        set_orientation: Orientation::Horizontal,
+                        set_spacing: 8,
+                        set_hexpand: true,
+
+                        append: name_label = &Label {
+                            set_text: label,
+                            set_halign: Align::Start,
+                            set_width_chars: 9,
+                            set_xalign: 0.0,
+                        },
+                        append: &scale,
+                    }
+                };
+                self_.append(&row);
+                scale
+            })
+            .collect();
+
+        imp.css_provider.set(css_provider).unwrap();
+        imp.scales.set(scales).unwrap();
+        imp.on_change
+            .set(std::boxed::Box::new(on_change))
+            .unwrap_or_else(|_| unreachable!());
+
+        self_
+    }
+
+    /// Moves the sliders to `c`, unless it is the color they already show
+    pub fn set_color(&self, c: Lch, space: WorkingSpace) {
+        let imp = imp::LchSliders::from_instance(&self);
+        let (l, chroma, hue) = imp.components.get();
+        let shown = color::gamut_map(space.lch_from_components(l, chroma, hue), space);
+        let hex = |c: Lch| color::hex_from_srgb(c.into_color());
+        if space == imp.working_space.get() && hex(shown) == hex(c) {
+            return;
+        }
+        imp.working_space.set(space);
+
+        let mut components = space.lch_components(c);
+        if components.1 < NEUTRAL_CHROMA {
+            components.2 = hue;
+        }
+        imp.components.set(components);
+
+        imp.updating.set(true);
+        for (channel, scale) in LchChannel::ALL.iter().zip(imp.scales.get().unwrap()) {
+            scale.set_value(channel.get(components).into());
+        }
+        imp.updating.set(false);
+        self.set_gradients();
+    }
+
+    fn on_value_changed(&self, channel: LchChannel, v: f32) {
+        let imp = imp::LchSliders::from_instance(&self);
+        if imp.updating.get() {
+            return;
+        }
+        let components = channel.set(imp.components.get(), v);
+        imp.components.set(components);
+        self.set_gradients();
+
+        let space = imp.working_space.get();
+        let (l, chroma, hue) = components;
+        let c = color::gamut_map(space.lch_from_components(l, chroma, hue), space);
+        (imp.on_change.get().unwrap())(c);
+    }
+
+    /// colors each track with the colors the slider would select, for the current other components
+    fn set_gradients(&self) {
+        let imp = imp::LchSliders::from_instance(&self);
+        let space = imp.working_space.get();
+        let (l, chroma, hue) = imp.components.get();
+        let c = space.lch_from_components(l, chroma, hue);
+
+        let mut css = String::new();
+        for channel in LchChannel::ALL {
+            let stops = color::channel_gradient(c, channel, space, GRADIENT_STOPS)
+                .into_iter()
+                .map(|s| format!("#{}", color::hex_from_srgb(s)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            css += &format!(
+                "#{} trough {{ background-image: linear-gradient(to right, {stops}); }}\n",
+                channel_name(channel).1
+            );
+        }
+        imp.css_provider
+            .get()
+            .unwrap()
+            .load_from_data(css.as_bytes());
+    }
+}
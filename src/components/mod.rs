@@ -1 +1,8 @@
 pub mod accent_editor;
+pub mod accent_swatches;
+pub mod color_readout;
+pub mod contrast_report;
+pub mod lch_sliders;
+pub mod palette_editor;
+pub mod tonal_scale;
+pub mod widget_gallery;
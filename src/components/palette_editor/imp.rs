@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{glib, subclass::prelude::*, Box, Entry, ListBox};
+use hue_chroma_accent_core::{
+    accent_palette::PaletteSet,
+    color::{Backgrounds, ContrastAlgorithm},
+};
+use once_cell::sync::OnceCell;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+// Object holding the state
+#[derive(Default)]
+pub struct PaletteEditor {
+    pub palette_set: Rc<RefCell<Option<PaletteSet>>>,
+    pub is_dark: Rc<Cell<bool>>,
+    /// the backgrounds of the light & dark color scheme
+    pub backgrounds: Rc<OnceCell<[Backgrounds; 2]>>,
+    pub contrast_algorithm: Rc<Cell<ContrastAlgorithm>>,
+    pub name_entry: Rc<OnceCell<Entry>>,
+    pub entry_list: Rc<OnceCell<ListBox>>,
+    pub on_saved: Rc<OnceCell<std::boxed::Box<dyn Fn(&PaletteSet)>>>,
+}
+
+// The central trait for subclassing a GObject
+#[glib::object_subclass]
+impl ObjectSubclass for PaletteEditor {
+    const NAME: &'static str = "PaletteEditorWidget";
+    type Type = super::PaletteEditor;
+    type ParentType = Box;
+}
+
+// Trait shared by all GObjects
+impl ObjectImpl for PaletteEditor {}
+
+// Trait shared by all widgets
+impl WidgetImpl for PaletteEditor {}
+
+// Trait shared by all boxes
+impl BoxImpl for PaletteEditor {}
@@ -0,0 +1,351 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{
+    self, glib, subclass::prelude::*, Box, Button, ColorButton, Entry, Label, ListBox, Orientation,
+    ScrolledWindow, ToggleButton, Window,
+};
+use adw::prelude::*;
+use cascade::cascade;
+use hue_chroma_accent_core::{
+    accent_palette::{AccentPalette, PaletteSet},
+    color::{Backgrounds, ContrastAlgorithm},
+};
+use palette::{IntoColor, Srgb};
+use relm4_macros::view;
+
+use crate::{
+    palette_store,
+    util::{self, SRGB},
+};
+mod imp;
+
+glib::wrapper! {
+    pub struct PaletteEditor(ObjectSubclass<imp::PaletteEditor>)
+        @extends gtk::Box, gtk::Widget,
+    @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl PaletteEditor {
+    pub fn new<F: Fn(&PaletteSet) + 'static>(
+        palette_set: PaletteSet,
+        backgrounds: [Backgrounds; 2],
+        is_dark: bool,
+        contrast_algorithm: ContrastAlgorithm,
+        on_saved: F,
+    ) -> Self {
+        let self_: Self = glib::Object::new(&[]).expect("Failed to create Palette Editor Widget");
+
+        let imp = imp::PaletteEditor::from_instance(&self_);
+
+        cascade! {
+            &self_;
+            ..set_orientation(Orientation::Vertical);
+        };
+
+        view! {
+            inner = Box {
+                set_orientation: Orientation::Vertical,
+                set_spacing: 4,
+                set_margin_top: 4,
+                set_margin_bottom: 4,
+                set_margin_start: 4,
+                set_margin_end: 4,
+                set_hexpand: true,
+
+                append: name_box = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 4,
+                    set_margin_top: 4,
+                    set_margin_bottom: 4,
+                    set_margin_start: 4,
+                    set_margin_end: 4,
+                    set_hexpand: true,
+
+                    append: name_label = &Label {
+                        set_text: "Name",
+                    },
+                    append: name_entry = &Entry {
+                        set_text: &palette_set.name,
+                        set_hexpand: true,
+                    },
+                },
+
+                append: color_scheme_box = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 4,
+                    set_margin_top: 4,
+                    set_margin_bottom: 4,
+                    set_margin_start: 4,
+                    set_margin_end: 4,
+                    set_hexpand: true,
+
+                    append: light_color_scheme = &ToggleButton {
+                        set_child: Some(&Label::new(Some("Light"))),
+                        set_active: !is_dark,
+                    },
+                    append: dark_color_scheme = &ToggleButton {
+                        set_child: Some(&Label::new(Some("Dark"))),
+                        set_group: Some(&light_color_scheme),
+                        set_active: is_dark,
+                    },
+                },
+
+                append: entry_list = &ListBox {
+                    add_css_class: "boxed-list",
+                },
+
+                append: buttons_box = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 4,
+                    set_margin_top: 4,
+                    set_margin_bottom: 4,
+                    set_margin_start: 4,
+                    set_margin_end: 4,
+                    set_hexpand: true,
+
+                    append: add_button = &Button {
+                        set_label: "Add",
+                    },
+                    append: save_button = &Button {
+                        set_label: "Save",
+                        add_css_class: "suggested-action",
+                    },
+                },
+            }
+        };
+
+        let scroll_window = ScrolledWindow::builder()
+            .hexpand(true)
+            .vexpand(true)
+            .child(&inner)
+            .build();
+        self_.append(&scroll_window);
+
+        name_entry.connect_changed(glib::clone!(@weak self_ => move |e| {
+            let imp = imp::PaletteEditor::from_instance(&self_);
+            if let Some(palette_set) = imp.palette_set.borrow_mut().as_mut() {
+                palette_set.name = e.text().to_string();
+            }
+        }));
+        light_color_scheme.connect_toggled(glib::clone!(@weak self_ => move |t| {
+            if t.is_active() {
+                self_.set_color_scheme(false);
+            }
+        }));
+        dark_color_scheme.connect_toggled(glib::clone!(@weak self_ => move |t| {
+            if t.is_active() {
+                self_.set_color_scheme(true);
+            }
+        }));
+        add_button.connect_clicked(glib::clone!(@weak self_ => move |_| {
+            self_.add_entry();
+        }));
+        save_button.connect_clicked(glib::clone!(@weak self_ => move |_| {
+            self_.save();
+        }));
+
+        imp.palette_set.replace(Some(palette_set));
+        imp.is_dark.set(is_dark);
+        imp.backgrounds.set(backgrounds).unwrap();
+        imp.contrast_algorithm.set(contrast_algorithm);
+        imp.name_entry.set(name_entry).unwrap();
+        imp.entry_list.set(entry_list).unwrap();
+        imp.on_saved
+            .set(std::boxed::Box::new(on_saved))
+            .unwrap_or_else(|_| unreachable!());
+        // set widget state
+
+        self_.set_entry_rows();
+
+        self_
+    }
+
+    fn set_color_scheme(&self, is_dark: bool) {
+        let imp = imp::PaletteEditor::from_instance(&self);
+        imp.is_dark.set(is_dark);
+        self.set_entry_rows();
+    }
+
+    /// the entries of the palette for the selected color scheme
+    fn entries(&self) -> Vec<AccentPalette> {
+        let imp = imp::PaletteEditor::from_instance(&self);
+        imp.palette_set
+            .borrow()
+            .as_ref()
+            .map(|s| s.palette(imp.is_dark.get()).to_vec())
+            .unwrap_or_default()
+    }
+
+    fn update_entries<F: FnOnce(&mut Vec<AccentPalette>)>(&self, f: F) {
+        let imp = imp::PaletteEditor::from_instance(&self);
+        if let Some(palette_set) = imp.palette_set.borrow_mut().as_mut() {
+            f(palette_set.palette_mut(imp.is_dark.get()));
+        }
+    }
+
+    fn set_entry_rows(&self) {
+        let imp = imp::PaletteEditor::from_instance(&self);
+        let entry_list = imp.entry_list.get().unwrap();
+
+        while let Some(c) = entry_list.first_child() {
+            entry_list.remove(&c);
+        }
+
+        let entries = self.entries();
+        for (i, entry) in entries.iter().enumerate() {
+            entry_list.append(&self.entry_row(i, entry, entries.len()));
+        }
+    }
+
+    fn entry_row(&self, i: usize, entry: &AccentPalette, len: usize) -> Box {
+        let contrast_label = Label::new(Some(&self.contrast_text(entry)));
+
+        let accent_button = cascade! {
+            ColorButton::with_rgba(&SRGB(entry.accent_color).into());
+            ..set_title("Accent Color as text");
+            ..set_tooltip_text(Some("Accent Color as text"));
+        };
+        let bg_button = cascade! {
+            ColorButton::with_rgba(&SRGB(entry.accent_color_bg).into());
+            ..set_title("Accent Background");
+            ..set_tooltip_text(Some("Accent Background"));
+        };
+        let fg_button = cascade! {
+            ColorButton::with_rgba(&SRGB(entry.accent_color_fg).into());
+            ..set_title("Text on Accent");
+            ..set_tooltip_text(Some("Text on Accent"));
+        };
+
+        view! {
+            row = Box {
+                set_orientation: Orientation::Horizontal,
+                set_spacing: 4,
+                set_margin_top: 4,
+                set_margin_bottom: 4,
+                set_margin_start: 4,
+                set_margin_end: 4,
+                set_hexpand: true,
+
+                append: name_entry = &Entry {
+                    set_text: &entry.name,
+                    set_hexpand: true,
+                    connect_changed: glib::clone!(@weak self as self_ => move |e| {
+                        let name = e.text().to_string();
+                        self_.update_entries(|entries| entries[i].name = name);
+                    }),
+                },
+                append: &accent_button,
+                append: &bg_button,
+                append: &fg_button,
+                append: &contrast_label,
+                append: up_button = &Button {
+                    set_icon_name: "go-up-symbolic",
+                    set_tooltip_text: Some("Move Up"),
+                    set_sensitive: i > 0,
+                    connect_clicked: glib::clone!(@weak self as self_ => move |_| {
+                        self_.update_entries(|entries| entries.swap(i - 1, i));
+                        self_.set_entry_rows();
+                    }),
+                },
+                append: down_button = &Button {
+                    set_icon_name: "go-down-symbolic",
+                    set_tooltip_text: Some("Move Down"),
+                    set_sensitive: i + 1 < len,
+                    connect_clicked: glib::clone!(@weak self as self_ => move |_| {
+                        self_.update_entries(|entries| entries.swap(i, i + 1));
+                        self_.set_entry_rows();
+                    }),
+                },
+                append: remove_button = &Button {
+                    set_icon_name: "list-remove-symbolic",
+                    set_tooltip_text: Some("Remove"),
+                    connect_clicked: glib::clone!(@weak self as self_ => move |_| {
+                        self_.update_entries(|entries| {
+                            entries.remove(i);
+                        });
+                        self_.set_entry_rows();
+                    }),
+                },
+            }
+        };
+
+        let set_color = |button: &ColorButton, f: fn(&mut AccentPalette, Srgb)| {
+            button.connect_rgba_notify(
+                glib::clone!(@weak self as self_, @weak contrast_label => move |b| {
+                    let c = SRGB::from(b.rgba()).into();
+                    self_.update_entries(|entries| f(&mut entries[i], c));
+                    if let Some(entry) = self_.entries().get(i) {
+                        contrast_label.set_text(&self_.contrast_text(entry));
+                    }
+                }),
+            );
+        };
+        set_color(&accent_button, |e, c| e.accent_color = c);
+        set_color(&bg_button, |e, c| e.accent_color_bg = c);
+        set_color(&fg_button, |e, c| e.accent_color_fg = c);
+
+        row
+    }
+
+    /// contrast of the text on the accent and of the accent as text against the window
+    fn contrast_text(&self, entry: &AccentPalette) -> String {
+        let imp = imp::PaletteEditor::from_instance(&self);
+        let algorithm = imp.contrast_algorithm.get();
+        let is_dark = imp.is_dark.get();
+        let base = imp.backgrounds.get().unwrap()[is_dark as usize].worst_case(is_dark);
+
+        format!(
+            "On accent {:.1} · As text {:.1}",
+            algorithm.contrast(
+                entry.accent_color_fg.into_color(),
+                entry.accent_color_bg.into_color()
+            ),
+            algorithm.contrast(entry.accent_color.into_color(), base)
+        )
+    }
+
+    fn add_entry(&self) {
+        self.update_entries(|entries| {
+            let entry = entries.last().cloned().unwrap_or_else(|| AccentPalette {
+                name: String::new(),
+                accent_color: Srgb::new(0.11, 0.443, 0.847),
+                accent_color_bg: Srgb::new(0.208, 0.518, 0.894),
+                accent_color_fg: Srgb::new(1.0, 1.0, 1.0),
+            });
+            entries.push(AccentPalette {
+                name: format!("ACCENT_{}", entries.len() + 1),
+                ..entry
+            });
+        });
+        self.set_entry_rows();
+    }
+
+    fn save(&self) {
+        let imp = imp::PaletteEditor::from_instance(&self);
+
+        let res = match imp.palette_set.borrow_mut().as_mut() {
+            Some(palette_set) => {
+                palette_store::save(palette_set).map(|path| (path, palette_set.clone()))
+            }
+            None => return,
+        };
+        match res {
+            Ok((path, palette_set)) => {
+                log::info!("Saved palette to {}", path.display());
+                (imp.on_saved.get().unwrap())(&palette_set);
+                if let Some(window) = self.root_window() {
+                    window.close();
+                }
+            }
+            Err(err) => {
+                log::warn!("Failed to save palette, {}", &err);
+                // the editor stays open, so the palette can be saved again
+                util::show_error(self, "Failed to Save Palette", &err);
+            }
+        }
+    }
+
+    fn root_window(&self) -> Option<Window> {
+        self.root().and_then(|r| r.downcast::<Window>().ok())
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{glib, subclass::prelude::*, Box, CssProvider};
+use hue_chroma_accent_core::{
+    accent_palette::PaletteSet,
+    color::{Backgrounds, ContrastAlgorithm, TonalSteps, WorkingSpace},
+};
+use once_cell::sync::OnceCell;
+use palette::Lch;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+// Object holding the state
+#[derive(Default)]
+pub struct TonalScale {
+    pub css_provider: Rc<OnceCell<CssProvider>>,
+    pub swatch_box: Rc<OnceCell<Box>>,
+    pub steps: Rc<Cell<TonalSteps>>,
+    pub color: Rc<Cell<Option<Lch>>>,
+    pub ramp: Rc<RefCell<Vec<Lch>>>,
+    pub contrast_algorithm: Rc<Cell<ContrastAlgorithm>>,
+    pub working_space: Rc<Cell<WorkingSpace>>,
+    /// the backgrounds of the light & dark color scheme
+    pub backgrounds: Rc<OnceCell<[Backgrounds; 2]>>,
+    pub on_save: Rc<OnceCell<std::boxed::Box<dyn Fn(PaletteSet)>>>,
+}
+
+// The central trait for subclassing a GObject
+#[glib::object_subclass]
+impl ObjectSubclass for TonalScale {
+    const NAME: &'static str = "TonalScaleWidget";
+    type Type = super::TonalScale;
+    type ParentType = Box;
+}
+
+// Trait shared by all GObjects
+impl ObjectImpl for TonalScale {}
+
+// Trait shared by all widgets
+impl WidgetImpl for TonalScale {}
+
+// Trait shared by all boxes
+impl BoxImpl for TonalScale {}
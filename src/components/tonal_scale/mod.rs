@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{
+    self, gdk, glib, subclass::prelude::*, Align, Box, Button, CssProvider, Label, Orientation,
+    StyleContext, ToggleButton,
+};
+use adw::prelude::*;
+use cascade::cascade;
+use hue_chroma_accent_core::{
+    accent_palette::{AccentPalette, PaletteSet},
+    color::{self, Backgrounds, ContrastAlgorithm, TonalSteps, WorkingSpace},
+};
+use palette::{IntoColor, Lch};
+use relm4_macros::view;
+mod imp;
+
+glib::wrapper! {
+    pub struct TonalScale(ObjectSubclass<imp::TonalScale>)
+        @extends gtk::Box, gtk::Widget,
+    @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl TonalScale {
+    /// `backgrounds` are the light & dark backgrounds the tones are checked & saved against
+    pub fn new<F: Fn(PaletteSet) + 'static>(backgrounds: [Backgrounds; 2], on_save: F) -> Self {
+        let self_: Self = glib::Object::new(&[]).expect("Failed to create Tonal Scale Widget");
+
+        let imp = imp::TonalScale::from_instance(&self_);
+
+        cascade! {
+            &self_;
+            ..set_orientation(Orientation::Vertical);
+        };
+
+        let css_provider = CssProvider::new();
+        StyleContext::add_provider_for_display(
+            &gdk::Display::default().expect("Error initializing GTK CSS provider."),
+            &css_provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+
+        view! {
+            header_box = Box {
+                set_orientation: Orientation::Horizontal,
+                set_spacing: 4,
+                set_margin_top: 4,
+                set_margin_bottom: 4,
+                set_margin_start: 4,
+                set_margin_end: 4,
+                set_hexpand: true,
+
+                append: title = &Label {
+                    set_text: "Tonal Scale",
+                },
+                append: twelve_toggle = &ToggleButton {
+                    set_child: Some(&Label::new(Some("12 Steps"))),
+                    set_active: true,
+                },
+                append: tones_toggle = &ToggleButton {
+                    set_child: Some(&Label::new(Some("Tones 0–100"))),
+                    set_group: Some(&twelve_toggle),
+                },
+                append: save_button = &Button {
+                    set_label: "Save as Palette",
+                    set_halign: Align::End,
+                    set_hexpand: true,
+                },
+            }
+        };
+        view! {
+            swatch_box = Box {
+                set_orientation: Orientation::Horizontal,
+                set_homogeneous: true,
+                set_margin_top: 4,
+                set_margin_bottom: 4,
+                set_margin_start: 4,
+                set_margin_end: 4,
+                set_hexpand: true,
+            }
+        };
+        self_.append(&header_box);
+        self_.append(&swatch_box);
+
+        twelve_toggle.connect_toggled(glib::clone!(@weak self_ => move |t| {
+            if t.is_active() {
+                self_.set_steps(TonalSteps::Twelve);
+            }
+        }));
+        tones_toggle.connect_toggled(glib::clone!(@weak self_ => move |t| {
+            if t.is_active() {
+                self_.set_steps(TonalSteps::Tones);
+            }
+        }));
+        save_button.connect_clicked(glib::clone!(@weak self_ => move |_| {
+            self_.save();
+        }));
+
+        imp.css_provider.set(css_provider).unwrap();
+        imp.swatch_box.set(swatch_box).unwrap();
+        imp.on_save
+            .set(std::boxed::Box::new(on_save))
+            .unwrap_or_else(|_| unreachable!());
+        imp.backgrounds.set(backgrounds).unwrap();
+
+        self_
+    }
+
+    pub fn set_color(&self, c: Lch, algorithm: ContrastAlgorithm, space: WorkingSpace) {
+        let imp = imp::TonalScale::from_instance(&self);
+        imp.color.set(Some(c));
+        imp.contrast_algorithm.set(algorithm);
+        imp.working_space.set(space);
+        self.set_swatches();
+    }
+
+    fn set_steps(&self, steps: TonalSteps) {
+        let imp = imp::TonalScale::from_instance(&self);
+        imp.steps.set(steps);
+        self.set_swatches();
+    }
+
+    fn set_swatches(&self) {
+        let imp = imp::TonalScale::from_instance(&self);
+        let swatch_box = imp.swatch_box.get().unwrap();
+        let c = match imp.color.get() {
+            Some(c) => c,
+            None => return,
+        };
+        let algorithm = imp.contrast_algorithm.get();
+        let space = imp.working_space.get();
+        let steps = imp.steps.get();
+
+        let ramp = color::tonal_scale(c, steps, space);
+        let [light, dark] = imp.backgrounds.get().unwrap();
+        let light_bg = light.worst_case(false);
+        let dark_bg = dark.worst_case(true);
+        let (light_target, _) = algorithm.accent_contrast(false);
+        let (dark_target, _) = algorithm.accent_contrast(true);
+
+        while let Some(c) = swatch_box.first_child() {
+            swatch_box.remove(&c);
+        }
+
+        let mut css = String::new();
+        for (i, (tone, c)) in steps.tones().into_iter().zip(&ramp).enumerate() {
+            let light_contrast = algorithm.contrast(*c, light_bg);
+            let dark_contrast = algorithm.contrast(*c, dark_bg);
+            let marks = [
+                (light_contrast >= light_target, "☀"),
+                (dark_contrast >= dark_target, "☾"),
+            ]
+            .iter()
+            .filter_map(|(passes, mark)| passes.then_some(*mark))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+            view! {
+                swatch = Box {
+                    set_orientation: Orientation::Vertical,
+                    set_widget_name: &format!("tone-{i}"),
+                    set_tooltip_text: Some(&format!(
+                        "Contrast as text on light {light_contrast:.1}, on dark {dark_contrast:.1}"
+                    )),
+
+                    append: tone_label = &Label {
+                        set_text: &format!("{tone:.0}"),
+                    },
+                    append: marks_label = &Label {
+                        set_text: &marks,
+                    },
+                }
+            };
+            swatch_box.append(&swatch);
+
+            let text = color::derive_color(*c, *c, None, None, algorithm, space).unwrap();
+            css += &format!(
+                "#tone-{i} {{ background-color: #{}; color: #{}; }}\n",
+                color::hex_from_srgb(c.into_color()),
+                color::hex_from_srgb(text.into_color())
+            );
+        }
+        imp.css_provider
+            .get()
+            .unwrap()
+            .load_from_data(css.as_bytes());
+        imp.ramp.replace(ramp);
+    }
+
+    /// passes the ramp as a palette set, with the roles of each tone derived for light & dark
+    fn save(&self) {
+        let imp = imp::TonalScale::from_instance(&self);
+        let c = match imp.color.get() {
+            Some(c) => c,
+            None => return,
+        };
+        let algorithm = imp.contrast_algorithm.get();
+        let space = imp.working_space.get();
+        let steps = imp.steps.get();
+        // saving reloads the palettes, which sets the swatches again
+        let ramp = imp.ramp.borrow().clone();
+
+        let entries = |is_dark: bool| {
+            let base = imp.backgrounds.get().unwrap()[is_dark as usize].worst_case(is_dark);
+            steps
+                .tones()
+                .into_iter()
+                .zip(ramp.iter())
+                .enumerate()
+                .map(|(i, (tone, c))| {
+                    let name = match steps {
+                        TonalSteps::Twelve => format!("STEP_{}", i + 1),
+                        TonalSteps::Tones => format!("TONE_{tone:.0}"),
+                    };
+                    AccentPalette::from_bg(name, c.into_color(), base, is_dark, algorithm, space)
+                })
+                .collect()
+        };
+        let palette_set = PaletteSet {
+            id: String::new(),
+            name: format!("Tones of #{}", color::hex_from_srgb(c.into_color())),
+            light: entries(false),
+            dark: entries(true),
+        };
+
+        (imp.on_save.get().unwrap())(palette_set);
+    }
+}
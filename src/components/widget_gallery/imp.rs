@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::{
+    gtk::{glib, subclass::prelude::*, Box},
+    ToastOverlay,
+};
+use once_cell::sync::OnceCell;
+use std::rc::Rc;
+
+// Object holding the state
+#[derive(Default)]
+pub struct WidgetGallery {
+    pub toast_overlay: Rc<OnceCell<ToastOverlay>>,
+}
+
+// The central trait for subclassing a GObject
+#[glib::object_subclass]
+impl ObjectSubclass for WidgetGallery {
+    const NAME: &'static str = "WidgetGalleryWidget";
+    type Type = super::WidgetGallery;
+    type ParentType = Box;
+}
+
+// Trait shared by all GObjects
+impl ObjectImpl for WidgetGallery {}
+
+// Trait shared by all widgets
+impl WidgetImpl for WidgetGallery {}
+
+// Trait shared by all boxes
+impl BoxImpl for WidgetGallery {}
@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{
+    self, glib, subclass::prelude::*, Align, Box, Button, CheckButton, Entry, Label, LevelBar,
+    LinkButton, ListBox, ListBoxRow, Orientation, ProgressBar, Scale, Spinner, StateFlags, Switch,
+    ToggleButton,
+};
+use adw::{prelude::*, Toast, ToastOverlay};
+use cascade::cascade;
+use relm4_macros::view;
+
+mod imp;
+
+glib::wrapper! {
+    pub struct WidgetGallery(ObjectSubclass<imp::WidgetGallery>)
+        @extends gtk::Box, gtk::Widget,
+    @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl Default for WidgetGallery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WidgetGallery {
+    /// The accented widgets of libadwaita, styled by the `@define-color` rules of the accent
+    pub fn new() -> Self {
+        let self_: Self = glib::Object::new(&[]).expect("Failed to create Widget Gallery Widget");
+
+        let imp = imp::WidgetGallery::from_instance(&self_);
+
+        cascade! {
+            &self_;
+            ..set_orientation(Orientation::Vertical);
+        };
+
+        let accented_button = cascade! {
+            Button::with_label("Demo Button");
+            ..add_css_class("suggested-action");
+        };
+        let check_button = cascade! {
+            CheckButton::with_label("Check");
+            ..set_active(true);
+        };
+        let radio_button = cascade! {
+            CheckButton::with_label("Radio");
+            ..set_active(true);
+        };
+        let other_radio_button = cascade! {
+            CheckButton::with_label("Other Radio");
+            ..set_group(Some(&radio_button));
+        };
+        let progress_bar = cascade! {
+            ProgressBar::new();
+            ..set_fraction(0.6);
+            ..set_valign(Align::Center);
+            ..set_hexpand(true);
+        };
+        let level_bar = cascade! {
+            LevelBar::for_interval(0.0, 1.0);
+            ..set_value(0.4);
+            ..set_valign(Align::Center);
+            ..set_hexpand(true);
+        };
+        let scale = cascade! {
+            Scale::with_range(Orientation::Horizontal, 0.0, 100.0, 1.0);
+            ..set_value(60.0);
+            ..set_hexpand(true);
+        };
+        let link_button =
+            LinkButton::with_label("https://gnome.pages.gitlab.gnome.org/libadwaita/", "Link");
+        let entry = cascade! {
+            Entry::new();
+            ..set_text("Focused entry");
+            ..set_hexpand(true);
+            // the ring of a focused entry is accented, but only one widget can have the focus
+            // the focus-within flag isn't bound before GTK 4.12, so the entry looks focused itself
+            ..set_state_flags(StateFlags::FOCUSED | StateFlags::FOCUS_VISIBLE, false);
+        };
+        let spinner = cascade! {
+            Spinner::new();
+            ..start();
+        };
+
+        let list_box = cascade! {
+            ListBox::new();
+            ..add_css_class("boxed-list");
+        };
+        for name in ["Selected Row", "Other Row"] {
+            let row = cascade! {
+                ListBoxRow::new();
+                ..set_child(Some(&cascade! {
+                    Label::new(Some(name));
+                    ..set_halign(Align::Start);
+                    ..set_margin_top(8);
+                    ..set_margin_bottom(8);
+                    ..set_margin_start(8);
+                    ..set_margin_end(8);
+                }));
+            };
+            list_box.append(&row);
+        }
+        list_box.select_row(list_box.row_at_index(0).as_ref());
+
+        view! {
+            inner = Box {
+                set_orientation: Orientation::Vertical,
+                set_spacing: 8,
+                set_margin_top: 4,
+                set_margin_bottom: 4,
+                set_margin_start: 4,
+                set_margin_end: 4,
+                set_hexpand: true,
+
+                append: buttons_row = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 8,
+
+                    append: &accented_button,
+                    append: toggle_button = &ToggleButton {
+                        set_label: "Toggle",
+                        set_active: true,
+                    },
+                    append: &link_button,
+                    append: accent_color_label = &Label {
+                        set_text: "Accent Color as text",
+                        add_css_class: "accented-text",
+                        add_css_class: "title-3",
+                    },
+                },
+                append: checks_row = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 8,
+
+                    append: switch = &Switch {
+                        set_active: true,
+                        set_valign: Align::Center,
+                    },
+                    append: &check_button,
+                    append: &radio_button,
+                    append: &other_radio_button,
+                    append: &spinner,
+                },
+                append: bars_row = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 8,
+
+                    append: &progress_bar,
+                    append: &level_bar,
+                },
+                append: &scale,
+                append: &entry,
+                append: &list_box,
+                // AdwBanner needs libadwaita 1.3, which the bindings don't cover yet
+                append: toast_button = &Button {
+                    set_label: "Show Toast",
+                    set_halign: Align::Start,
+                },
+            }
+        };
+
+        let toast_overlay = cascade! {
+            ToastOverlay::new();
+            ..set_child(Some(&inner));
+        };
+        self_.append(&toast_overlay);
+
+        toast_button.connect_clicked(glib::clone!(@weak self_ => move |_| {
+            self_.show_toast();
+        }));
+
+        imp.toast_overlay.set(toast_overlay).unwrap();
+
+        self_
+    }
+
+    fn show_toast(&self) {
+        let imp = imp::WidgetGallery::from_instance(&self);
+        imp.toast_overlay
+            .get()
+            .unwrap()
+            .add_toast(&Toast::new("Toasts are shown above the content"));
+    }
+}
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use hue_chroma_accent_core::color::{self, Accent};
+use std::{fs, io, path::PathBuf};
+
+const BLOCK_START: &str = "/* BEGIN hue-chroma-accent (managed block, do not edit) */";
+/// the start of a block after rules without a trailing newline, the newline was added with the block
+const BLOCK_START_AFTER_NEWLINE: &str =
+    "/* BEGIN hue-chroma-accent (managed block, do not edit, newline added) */";
+const BLOCK_END: &str = "/* END hue-chroma-accent */";
+
+/// Writes the accent into `$XDG_CONFIG_HOME/gtk-4.0/gtk.css`, replacing a previously applied block
+pub fn apply_gtk4(accent: &Accent) -> anyhow::Result<PathBuf> {
+    write_block("gtk-4.0", &accent.to_css())
+}
+
+/// Removes the managed block from `$XDG_CONFIG_HOME/gtk-4.0/gtk.css`
+/// `None` if there was no block to remove
+pub fn remove_gtk4() -> anyhow::Result<Option<PathBuf>> {
+    remove_block("gtk-4.0")
+}
+
+/// Writes the accent into `$XDG_CONFIG_HOME/gtk-3.0/gtk.css` using the legacy Adwaita color names
+pub fn apply_gtk3(accent: &Accent) -> anyhow::Result<PathBuf> {
+    write_block("gtk-3.0", &gtk3_css(accent))
+}
+
+/// Removes the managed block from `$XDG_CONFIG_HOME/gtk-3.0/gtk.css`
+/// `None` if there was no block to remove
+pub fn remove_gtk3() -> anyhow::Result<Option<PathBuf>> {
+    remove_block("gtk-3.0")
+}
+
+fn gtk3_css(accent: &Accent) -> String {
+    let accent_as_fg = color::hex_from_srgb(accent.accent);
+    let bg = color::hex_from_srgb(accent.accent_bg);
+    let fg = color::hex_from_srgb(accent.accent_fg);
+
+    // GTK 3 Adwaita doesn't know about accent_bg_color,
+    // so the selection colors are overridden as well
+    format!(
+        r#"
+@define-color accent_color #{accent_as_fg};
+@define-color accent_bg_color #{bg};
+@define-color accent_fg_color #{fg};
+@define-color theme_selected_bg_color #{bg};
+@define-color theme_selected_fg_color #{fg};
+@define-color theme_unfocused_selected_bg_color #{bg};
+@define-color theme_unfocused_selected_fg_color #{fg};
+@define-color selected_bg_color #{bg};
+@define-color selected_fg_color #{fg};
+@define-color link_color #{accent_as_fg};
+"#
+    )
+}
+
+fn gtk_css_path(gtk_dir: &str) -> anyhow::Result<PathBuf> {
+    let base_dirs = xdg::BaseDirectories::new()?;
+    Ok(base_dirs.place_config_file(format!("{gtk_dir}/gtk.css"))?)
+}
+
+fn read_css(path: &PathBuf) -> anyhow::Result<String> {
+    match fs::read_to_string(path) {
+        Ok(css) => Ok(css),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_block(gtk_dir: &str, block: &str) -> anyhow::Result<PathBuf> {
+    let path = gtk_css_path(gtk_dir)?;
+    fs::write(&path, with_block(&read_css(&path)?, block)?)?;
+    Ok(path)
+}
+
+/// Replaces the managed blocks of `css` with a single one holding `block`, at the end
+fn with_block(css: &str, block: &str) -> anyhow::Result<String> {
+    let mut css = strip_block(css)?;
+
+    let start = if !css.is_empty() && !css.ends_with('\n') {
+        css.push('\n');
+        BLOCK_START_AFTER_NEWLINE
+    } else {
+        BLOCK_START
+    };
+    css += &format!("{start}\n{}\n{BLOCK_END}\n", block.trim());
+    Ok(css)
+}
+
+fn remove_block(gtk_dir: &str) -> anyhow::Result<Option<PathBuf>> {
+    // unlike `gtk_css_path`, this doesn't create the directory of a missing file
+    let path = xdg::BaseDirectories::new()?
+        .get_config_home()
+        .join(format!("{gtk_dir}/gtk.css"));
+    let css = read_css(&path)?;
+    if find_block_start(&css).is_none() {
+        return Ok(None);
+    }
+    fs::write(&path, strip_block(&css)?)?;
+    Ok(Some(path))
+}
+
+/// The position of the first managed block in `css`, with whether a newline was added before it
+fn find_block_start(css: &str) -> Option<(usize, bool)> {
+    [(BLOCK_START, false), (BLOCK_START_AFTER_NEWLINE, true)]
+        .into_iter()
+        .filter_map(|(marker, added_newline)| Some((css.find(marker)?, added_newline)))
+        .min()
+}
+
+/// Removes every managed block from `css`, leaving the user's own rules untouched
+fn strip_block(css: &str) -> anyhow::Result<String> {
+    let mut stripped = String::with_capacity(css.len());
+    let mut rest = css;
+
+    while let Some((start, added_newline)) = find_block_start(rest) {
+        let end = match rest[start..].find(BLOCK_END) {
+            Some(end) => start + end + BLOCK_END.len(),
+            None => anyhow::bail!("Unterminated hue-chroma-accent block in gtk.css"),
+        };
+        let before = &rest[..start];
+        stripped += if added_newline {
+            before.strip_suffix('\n').unwrap_or(before)
+        } else {
+            before
+        };
+        rest = rest[end..].strip_prefix('\n').unwrap_or(&rest[end..]);
+    }
+    stripped += rest;
+
+    Ok(stripped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_CSS: &str = "/* my theme */\nwindow { padding: 2px; }\n\n\tlabel{color:red}";
+    const BLOCK: &str = "@define-color accent_color #1c71d8;";
+
+    fn managed(start: &str, block: &str) -> String {
+        format!("{start}\n{block}\n{BLOCK_END}\n")
+    }
+
+    #[test]
+    fn block_is_appended() {
+        assert_eq!(with_block("", BLOCK).unwrap(), managed(BLOCK_START, BLOCK));
+        assert_eq!(
+            with_block(&format!("{USER_CSS}\n"), BLOCK).unwrap(),
+            format!("{USER_CSS}\n{}", managed(BLOCK_START, BLOCK))
+        );
+        // the newline separating the block is marked, so removing the block removes it too
+        assert_eq!(
+            with_block(USER_CSS, BLOCK).unwrap(),
+            format!("{USER_CSS}\n{}", managed(BLOCK_START_AFTER_NEWLINE, BLOCK))
+        );
+    }
+
+    #[test]
+    fn existing_block_is_replaced() {
+        let old = "@define-color accent_color #e01b24;";
+        let css = format!(
+            "{USER_CSS}\n{}.extra {{ margin: 0; }}\n",
+            managed(BLOCK_START, old)
+        );
+        let css = with_block(&css, BLOCK).unwrap();
+        assert!(!css.contains(old));
+        assert_eq!(css.matches(BLOCK_START).count(), 1);
+        assert_eq!(
+            css,
+            format!(
+                "{USER_CSS}\n.extra {{ margin: 0; }}\n{}",
+                managed(BLOCK_START, BLOCK)
+            )
+        );
+    }
+
+    #[test]
+    fn reapplying_is_idempotent() {
+        let once = with_block(USER_CSS, BLOCK).unwrap();
+        assert_eq!(with_block(&once, BLOCK).unwrap(), once);
+    }
+
+    #[test]
+    fn surrounding_css_is_preserved() {
+        let before = "a { }\r\n  /* keep */ \n";
+        let after = "\n\n b { }  ";
+        let css = format!("{before}{}{after}", managed(BLOCK_START, BLOCK));
+        assert_eq!(strip_block(&css).unwrap(), format!("{before}{after}"));
+        assert_eq!(strip_block(USER_CSS).unwrap(), USER_CSS);
+    }
+
+    #[test]
+    fn removing_restores_the_css() {
+        for css in [
+            String::new(),
+            USER_CSS.to_string(),
+            format!("{USER_CSS}\n"),
+            format!("{USER_CSS}\n\n"),
+        ] {
+            let applied = with_block(&css, BLOCK).unwrap();
+            assert_eq!(strip_block(&applied).unwrap(), css, "{css:?}");
+        }
+    }
+
+    #[test]
+    fn missing_end_marker_is_an_error() {
+        for start in [BLOCK_START, BLOCK_START_AFTER_NEWLINE] {
+            let css = format!("{USER_CSS}\n{start}\n{BLOCK}\n");
+            assert!(strip_block(&css).is_err());
+            assert!(with_block(&css, BLOCK).is_err());
+        }
+    }
+}
@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use std::time::{Duration, Instant};
+
+/// changes from the same source closer together than this are undone at once, e.g. dragging a slider
+const MERGE_WINDOW: Duration = Duration::from_millis(500);
+/// the number of states that can be undone
+const MAX_UNDO: usize = 100;
+
+/// An undo/redo stack of states, `K` names the sources of changes that can be merged
+#[derive(Debug)]
+pub struct History<T, K = ()> {
+    undo: Vec<T>,
+    current: Option<T>,
+    redo: Vec<T>,
+    last_change: Option<(K, Instant)>,
+}
+
+impl<T, K> Default for History<T, K> {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            current: None,
+            redo: Vec::new(),
+            last_change: None,
+        }
+    }
+}
+
+impl<T: Clone + PartialEq, K: PartialEq> History<T, K> {
+    /// Records `state` as the current state, unless it already is, and forgets the undone states
+    ///
+    /// It replaces the current state if the last change came from the same `source` within
+    /// `MERGE_WINDOW`, a `None` source is never merged, e.g. a click
+    pub fn record(&mut self, state: T, source: Option<K>, now: Instant) {
+        if self.current.as_ref() == Some(&state) {
+            return;
+        }
+        let merge = match (&self.last_change, &source) {
+            (Some((last, t)), Some(source)) => {
+                last == source && now.duration_since(*t) < MERGE_WINDOW
+            }
+            _ => false,
+        };
+        if let Some(previous) = self.current.replace(state) {
+            if !merge {
+                self.undo.push(previous);
+                if self.undo.len() > MAX_UNDO {
+                    self.undo.remove(0);
+                }
+            }
+        }
+        self.redo.clear();
+        self.last_change = source.map(|source| (source, now));
+    }
+
+    /// Steps back to the previous state & returns it
+    pub fn undo(&mut self) -> Option<T> {
+        let previous = self.undo.pop()?;
+        if let Some(current) = self.current.replace(previous.clone()) {
+            self.redo.push(current);
+        }
+        // the next change starts a new step
+        self.last_change = None;
+        Some(previous)
+    }
+
+    /// Steps forward to the last undone state & returns it
+    pub fn redo(&mut self) -> Option<T> {
+        let next = self.redo.pop()?;
+        if let Some(current) = self.current.replace(next.clone()) {
+            self.undo.push(current);
+        }
+        self.last_change = None;
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// records each state a second after the previous one
+    fn history(states: &[u32]) -> (History<u32, &'static str>, Instant) {
+        let mut history = History::default();
+        let mut now = Instant::now();
+        for state in states {
+            history.record(*state, Some("slider"), now);
+            now += Duration::from_secs(1);
+        }
+        (history, now)
+    }
+
+    #[test]
+    fn undo_and_redo_step_through_states() {
+        let (mut history, now) = history(&[1, 2, 3]);
+        assert_eq!(history.undo(), Some(2));
+        assert_eq!(history.undo(), Some(1));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(2));
+        assert!(history.can_undo() && history.can_redo());
+
+        // a new change forgets the undone states
+        history.record(4, None, now);
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(), Some(2));
+    }
+
+    #[test]
+    fn quick_changes_are_merged() {
+        let (mut history, now) = history(&[1, 2]);
+        history.record(3, Some("slider"), now + Duration::from_millis(100));
+        history.record(3, Some("slider"), now + Duration::from_millis(150));
+        history.record(4, Some("slider"), now + Duration::from_millis(200));
+        assert_eq!(history.undo(), Some(2));
+        assert_eq!(history.redo(), Some(4));
+    }
+
+    #[test]
+    fn quick_changes_from_other_sources_are_kept() {
+        let (mut history, now) = history(&[1]);
+        // two clicks, e.g. on palette entries
+        history.record(2, None, now + Duration::from_millis(100));
+        history.record(3, None, now + Duration::from_millis(200));
+        // a drag right after a click
+        history.record(4, Some("slider"), now + Duration::from_millis(250));
+        history.record(5, Some("entry"), now + Duration::from_millis(300));
+        assert_eq!(history.undo(), Some(4));
+        assert_eq!(history.undo(), Some(3));
+        assert_eq!(history.undo(), Some(2));
+        assert_eq!(history.undo(), Some(1));
+    }
+
+    #[test]
+    fn undo_is_limited() {
+        let states: Vec<u32> = (0..MAX_UNDO as u32 + 10).collect();
+        let (mut history, _) = history(&states);
+        let mut undone = 0;
+        while history.undo().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_UNDO);
+    }
+}
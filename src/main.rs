@@ -6,14 +6,21 @@ use components::accent_editor::AccentEditor;
 use config::{APP_ID, GETTEXT_PACKAGE, LOCALEDIR, PKGDATADIR, PROFILE, VERSION};
 use gettextrs::{gettext, LocaleCategory};
 
-mod accent_palette;
+mod cli;
 mod components;
 mod config;
+mod gtk_css;
+mod history;
+mod palette_store;
+mod saved_accents;
 mod util;
+mod wallpaper;
 
 fn setup_shortcuts(app: &Application) {
     //quit shortcut
     app.set_accels_for_action("win.quit", &["<primary>W", "Escape"]);
+    app.set_accels_for_action("editor.undo", &["<primary>Z"]);
+    app.set_accels_for_action("editor.redo", &["<primary><shift>Z"]);
 }
 
 fn load_css() -> CssProvider {
@@ -38,9 +45,16 @@ fn main() {
 
     glib::set_application_name(&gettext("Hue Chroma Accent"));
 
-    let app = Application::builder().application_id(APP_ID).build();
     gio::resources_register_include!("compiled.gresource").unwrap();
 
+    // headless commands run before anything connects to a display
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(|a| a.as_str()) == Some("derive") {
+        std::process::exit(cli::run(args[2..].to_vec()));
+    }
+
+    let app = Application::builder().application_id(APP_ID).build();
+
     app.connect_startup(|app| {
         setup_shortcuts(app);
     });
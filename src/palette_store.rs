@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::gio;
+use hue_chroma_accent_core::{
+    accent_palette::{self, PaletteSet},
+    color::{Backgrounds, ContrastAlgorithm, WorkingSpace},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const RESOURCE_PALETTES: &str = "/com/github/wash2/HueChromaAccent/palettes/";
+
+/// Loads the palettes bundled in the resources, followed by the ones in `$XDG_DATA_HOME/hue-chroma-accent/palettes/`
+pub fn load_palettes() -> Vec<PaletteSet> {
+    let mut sets = vec![];
+
+    let resources =
+        gio::resources_enumerate_children(RESOURCE_PALETTES, gio::ResourceLookupFlags::NONE)
+            .unwrap_or_default();
+    for name in resources {
+        let set = gio::resources_lookup_data(
+            &format!("{RESOURCE_PALETTES}{name}"),
+            gio::ResourceLookupFlags::NONE,
+        )
+        .map_err(anyhow::Error::from)
+        .and_then(|data| Ok(String::from_utf8(data.to_vec())?))
+        .and_then(|data| PaletteSet::parse(&name, &data));
+        match set {
+            Ok(set) => sets.push(set),
+            Err(err) => log::warn!("Failed to load bundled palette {}, {}", name, &err),
+        }
+    }
+
+    match user_palettes_dir().map(fs::read_dir) {
+        Ok(Ok(entries)) => {
+            let mut paths: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+            paths.sort();
+            for path in paths {
+                match PaletteSet::load(&path) {
+                    // user palettes replace bundled ones with the same id
+                    Ok(set) => match sets.iter_mut().find(|s| s.id == set.id) {
+                        Some(bundled) => *bundled = set,
+                        None => sets.push(set),
+                    },
+                    Err(err) => log::warn!("{:#}", &err),
+                }
+            }
+        }
+        // the directory doesn't have to exist
+        Ok(Err(_)) => {}
+        Err(err) => log::warn!("Failed to find user palettes, {}", &err),
+    }
+
+    sets
+}
+
+/// Saves the set to `$XDG_DATA_HOME/hue-chroma-accent/palettes/<id>.toml`
+/// sets without an id get one derived from their name
+pub fn save(set: &mut PaletteSet) -> anyhow::Result<PathBuf> {
+    if set.id.is_empty() {
+        set.id = set
+            .name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+            .collect();
+    }
+    if set.id.is_empty() {
+        anyhow::bail!("Palette needs a name to be saved");
+    }
+    // the accent editor has nothing to match to in a color scheme without colors
+    if set.light.is_empty() || set.dark.is_empty() {
+        anyhow::bail!("Palette needs colors for both the light & dark color scheme to be saved");
+    }
+
+    let dir = user_palettes_dir()?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.toml", set.id));
+    fs::write(
+        &path,
+        accent_palette::export(&path.to_string_lossy(), set, false)?,
+    )?;
+    Ok(path)
+}
+
+/// Imports a palette set from a `.gpl`, `.ase`, `.toml` or `.json` file
+/// roles missing from the file are derived against the light & dark `backgrounds` with `algorithm` in `space`
+pub fn import(
+    path: &Path,
+    backgrounds: &[Backgrounds; 2],
+    algorithm: ContrastAlgorithm,
+    space: WorkingSpace,
+) -> anyhow::Result<PaletteSet> {
+    accent_palette::import(
+        &path.to_string_lossy(),
+        &fs::read(path)?,
+        backgrounds,
+        algorithm,
+        space,
+    )
+}
+
+/// Exports a palette set to a `.gpl`, `.ase`, `.toml` or `.json` file
+/// `.gpl` files only hold the palette of one color scheme
+pub fn export(path: &Path, set: &PaletteSet, is_dark: bool) -> anyhow::Result<()> {
+    fs::write(
+        path,
+        accent_palette::export(&path.to_string_lossy(), set, is_dark)?,
+    )?;
+    Ok(())
+}
+
+/// `$XDG_DATA_HOME/hue-chroma-accent/palettes/`
+pub fn user_palettes_dir() -> anyhow::Result<PathBuf> {
+    let base_dirs = xdg::BaseDirectories::with_prefix("hue-chroma-accent")?;
+    Ok(base_dirs.get_data_home().join("palettes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_palette_parses() {
+        let set = PaletteSet::parse(
+            "gnome.toml",
+            include_str!("../data/resources/palettes/gnome.toml"),
+        )
+        .unwrap();
+        assert_eq!(set.id, "gnome");
+        assert_eq!(set.light.len(), 8);
+        assert_eq!(set.dark.len(), 8);
+    }
+
+    #[test]
+    fn palette_without_dark_colors_is_not_saved() {
+        let mut set = PaletteSet::parse(
+            "gnome.toml",
+            include_str!("../data/resources/palettes/gnome.toml"),
+        )
+        .unwrap();
+        set.dark.clear();
+        let err = save(&mut set).unwrap_err();
+        assert!(err.to_string().contains("light & dark"));
+    }
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::gio::{self, prelude::*};
+use hue_chroma_accent_core::color;
+use palette::Srgb;
+
+use crate::config::APP_ID;
+
+pub const RECENT_ACCENTS: &str = "recent-accents";
+pub const FAVORITE_ACCENTS: &str = "favorite-accents";
+/// the number of recent accents that are kept
+const MAX_RECENT: usize = 8;
+
+/// The settings of the app, `None` if its schema isn't installed, e.g. when running with cargo
+pub fn app_settings() -> Option<gio::Settings> {
+    let schema = gio::SettingsSchemaSource::default()?.lookup(APP_ID, true)?;
+    Some(gio::Settings::new_full(
+        &schema,
+        None::<&gio::SettingsBackend>,
+        None,
+    ))
+}
+
+/// The accents stored under `key`, invalid ones are skipped
+pub fn accents(settings: &gio::Settings, key: &str) -> Vec<Srgb> {
+    settings
+        .strv(key)
+        .iter()
+        .filter_map(|hex| color::srgb_from_hex(hex).ok())
+        .collect()
+}
+
+fn set_accents(settings: &gio::Settings, key: &str, accents: &[Srgb]) -> anyhow::Result<()> {
+    let hex: Vec<String> = accents
+        .iter()
+        .map(|c| format!("#{}", color::hex_from_srgb(*c)))
+        .collect();
+    settings.set_strv(key, &hex.iter().map(String::as_str).collect::<Vec<_>>())?;
+    Ok(())
+}
+
+/// colors are compared as they are stored
+fn same(c1: Srgb, c2: Srgb) -> bool {
+    color::hex_from_srgb(c1) == color::hex_from_srgb(c2)
+}
+
+/// `accents` with `c` in front, without duplicates & at most `max` long
+fn with_accent(accents: &[Srgb], c: Srgb, max: usize) -> Vec<Srgb> {
+    std::iter::once(c)
+        .chain(accents.iter().copied().filter(|a| !same(*a, c)))
+        .take(max)
+        .collect()
+}
+
+fn without_accent(accents: &[Srgb], c: Srgb) -> Vec<Srgb> {
+    accents.iter().copied().filter(|a| !same(*a, c)).collect()
+}
+
+pub fn add_recent(settings: &gio::Settings, c: Srgb) -> anyhow::Result<()> {
+    let recent = accents(settings, RECENT_ACCENTS);
+    set_accents(
+        settings,
+        RECENT_ACCENTS,
+        &with_accent(&recent, c, MAX_RECENT),
+    )
+}
+
+pub fn add_favorite(settings: &gio::Settings, c: Srgb) -> anyhow::Result<()> {
+    let favorites = accents(settings, FAVORITE_ACCENTS);
+    set_accents(
+        settings,
+        FAVORITE_ACCENTS,
+        &with_accent(&favorites, c, usize::MAX),
+    )
+}
+
+pub fn remove_favorite(settings: &gio::Settings, c: Srgb) -> anyhow::Result<()> {
+    let favorites = accents(settings, FAVORITE_ACCENTS);
+    set_accents(settings, FAVORITE_ACCENTS, &without_accent(&favorites, c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(accents: &[Srgb]) -> Vec<String> {
+        accents.iter().map(|c| color::hex_from_srgb(*c)).collect()
+    }
+
+    fn srgb(hex: &str) -> Srgb {
+        color::srgb_from_hex(hex).unwrap()
+    }
+
+    #[test]
+    fn added_accents_move_to_the_front() {
+        let accents = [srgb("3584e4"), srgb("e01b24"), srgb("33d17a")];
+        let added = with_accent(&accents, srgb("33d17a"), MAX_RECENT);
+        assert_eq!(hex(&added), ["33d17a", "3584e4", "e01b24"]);
+        let added = with_accent(&accents, srgb("f6d32d"), 3);
+        assert_eq!(hex(&added), ["f6d32d", "3584e4", "e01b24"]);
+    }
+
+    #[test]
+    fn removed_accents_are_gone() {
+        let accents = [srgb("3584e4"), srgb("e01b24")];
+        assert_eq!(hex(&without_accent(&accents, srgb("3584e4"))), ["e01b24"]);
+        assert_eq!(hex(&without_accent(&accents, srgb("33d17a"))).len(), 2);
+    }
+}
@@ -1,26 +1,28 @@
 // SPDX-License-Identifier: MPL-2.0-only
 
-use adw::gtk::gdk::RGBA;
-use float_cmp::approx_eq;
-use hex::encode;
-// use kmeans_colors::{get_kmeans_hamerly, Kmeans, Sort};
-use palette::{Clamp, IntoColor, Lch, Pixel, RelativeContrast, Srgb};
-use serde::{Deserialize, Serialize};
-
-#[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
-pub struct SRGB(pub Srgb);
+use adw::gtk::{
+    gdk::RGBA, gdk_pixbuf::Pixbuf, gio, prelude::*, ButtonsType, MessageDialog, MessageType,
+    Widget, Window,
+};
+use hue_chroma_accent_core::{
+    color::Backgrounds,
+    css_color::{self, ParseError},
+    extract::{self, AccentCandidate},
+};
+use palette::{IntoColor, Lch, Srgb};
+use std::path::Path;
 
-pub fn hex_from_rgba(rgba: RGBA) -> String {
-    let c = SRGB::from(rgba);
-    let hex = encode::<[u8; 3]>(Srgb::into_raw(c.0.into_format()));
-    format!("{hex}")
-}
+/// the style sheets libadwaita defines its colors in
+const ADWAITA_LIGHT_CSS: &str = "/org/gnome/Adwaita/styles/defaults-light.css";
+const ADWAITA_DARK_CSS: &str = "/org/gnome/Adwaita/styles/defaults-dark.css";
+
+/// images are downsampled to fit in this size before clustering
+const EXTRACT_SIZE: i32 = 128;
+
+/// Converts between the colors of the core library and GTK
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct SRGB(pub Srgb);
 
-impl SRGB {
-    pub fn into_inner(self) -> Srgb {
-        self.0
-    }
-}
 impl From<Srgb> for SRGB {
     fn from(c: Srgb) -> Self {
         Self(c)
@@ -44,60 +46,113 @@ impl Into<Srgb> for SRGB {
     }
 }
 
+/// Shows `err` in a modal dialog over the window of `widget`
+pub fn show_error(widget: &impl IsA<Widget>, title: &str, err: &anyhow::Error) {
+    let dialog = MessageDialog::builder()
+        .modal(true)
+        .buttons(ButtonsType::Close)
+        .message_type(MessageType::Error)
+        .text(title)
+        .secondary_text(&format!("{:#}", err))
+        .build();
+    let window = widget.root().and_then(|r| r.downcast::<Window>().ok());
+    dialog.set_transient_for(window.as_ref());
+    dialog.connect_response(|d, _| d.close());
+    dialog.show();
+}
+
 pub fn get_lch(c: RGBA) -> Lch {
     let c: Srgb = SRGB::from(c).into();
     c.into_format().into_color()
 }
 
-pub fn derive_color(
-    lch_color: Lch,
-    contrast: Option<f32>,
-    lighten: Option<bool>,
-) -> anyhow::Result<Lch> {
-    let mut lch_color_derived = lch_color.clone();
-    // lighten or darken
-    // TODO closed form solution using Lch color space contrast formula?
-    // for now do binary search...
-
-    if let Some(target_contrast) = contrast {
-        let (min, max) = match lighten {
-            Some(b) if b => (lch_color_derived.l, 100.0),
-            Some(_) => (0.0, lch_color_derived.l),
-            None => (0.0, 100.0),
-        };
-        let (mut l, mut r) = (min, max);
-
-        for _ in 0..100 {
-            let cur_guess_lightness = (l + r) / 2.0;
-            lch_color_derived.l = cur_guess_lightness;
-            let cur_contrast = lch_color.get_contrast_ratio(&lch_color_derived.into_color());
-            let move_away = target_contrast > cur_contrast;
-            let is_darker = lch_color.l < lch_color_derived.l;
-            if approx_eq!(f32, target_contrast, cur_contrast, ulps = 4) {
-                break;
-            } else if is_darker && move_away || !is_darker && !move_away {
-                l = cur_guess_lightness;
-            } else {
-                r = cur_guess_lightness;
-            }
+/// Parses a color in any CSS notation, the error has the char position of the offending input
+pub fn parse_color(s: &str) -> Result<SRGB, ParseError> {
+    css_color::parse(s).map(SRGB)
+}
+
+/// Reads the backgrounds of the color scheme from the style sheets of the installed libadwaita,
+/// the style context only has the backgrounds of the current scheme & updates them after a change
+pub fn adwaita_backgrounds(is_dark: bool) -> Backgrounds {
+    let path = if is_dark {
+        ADWAITA_DARK_CSS
+    } else {
+        ADWAITA_LIGHT_CSS
+    };
+    let defaults = Backgrounds::adwaita(is_dark);
+    match gio::resources_lookup_data(path, gio::ResourceLookupFlags::NONE) {
+        Ok(data) => defaults.with_css(&String::from_utf8_lossy(&data)),
+        Err(err) => {
+            log::warn!("Failed to read the libadwaita colors, {}", &err);
+            defaults
+        }
+    }
+}
+
+/// Extracts up to `count` candidate accents from the image at `path`, best first
+pub fn extract_accents(path: &Path, count: usize) -> anyhow::Result<Vec<AccentCandidate>> {
+    let pixbuf = Pixbuf::from_file_at_scale(path, EXTRACT_SIZE, EXTRACT_SIZE, true)?;
+    let bytes = pixbuf
+        .read_pixel_bytes()
+        .ok_or_else(|| anyhow::anyhow!("Failed to read the pixels of {}", path.display()))?;
+    let n_channels = pixbuf.n_channels() as usize;
+    let rowstride = pixbuf.rowstride() as usize;
+    let width = pixbuf.width() as usize;
+
+    // rows may be padded
+    let pixels: Vec<u8> = bytes
+        .chunks(rowstride)
+        .flat_map(|row| &row[..(width * n_channels).min(row.len())])
+        .copied()
+        .collect();
+
+    Ok(extract::accent_candidates_from_rgb8(
+        &pixels, n_channels, count,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hue_chroma_accent_core::color;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn srgb_round_trips_through_rgba(
+            r in 0.0f32..=1.0,
+            g in 0.0f32..=1.0,
+            b in 0.0f32..=1.0,
+        ) {
+            let c = SRGB(Srgb::new(r, g, b));
+            let rgba: RGBA = c.into();
+            prop_assert_eq!(rgba.alpha(), 1.0);
+            prop_assert_eq!(SRGB::from(rgba), c);
         }
 
-        // clamp to valid value in range
-        lch_color_derived.clamp_self();
+        #[test]
+        fn rgba_round_trips_through_srgb(
+            r in 0.0f32..=1.0,
+            g in 0.0f32..=1.0,
+            b in 0.0f32..=1.0,
+        ) {
+            let rgba = RGBA::new(r, g, b, 1.0);
+            let c: RGBA = SRGB::from(rgba).into();
+            prop_assert_eq!(c, rgba);
+        }
 
-        // verify contrast
-        let actual_contrast = lch_color_derived.get_contrast_ratio(&lch_color.into_color());
-        if !approx_eq!(f32, target_contrast, actual_contrast, ulps = 4) {
-            anyhow::bail!("Failed to derive color with contrast {}", target_contrast,);
+        #[test]
+        fn parsed_rgb_round_trips_through_rgba(r: u8, g: u8, b: u8) {
+            let rgba: RGBA = parse_color(&format!("rgb({r} {g} {b})")).unwrap().into();
+            let c: Srgb<u8> = Into::<Srgb>::into(SRGB::from(rgba)).into_format();
+            prop_assert_eq!(c, Srgb::new(r, g, b));
         }
 
-        Ok(lch_color_derived.into_color())
-    } else {
-        // maximize contrast if no constraint is given
-        if lch_color.l > 50.0 {
-            Ok(palette::named::BLACK.into_format().into_color())
-        } else {
-            Ok(palette::named::WHITE.into_format().into_color())
+        #[test]
+        fn hex_round_trips_through_rgba(r: u8, g: u8, b: u8) {
+            let hex = format!("{r:02x}{g:02x}{b:02x}");
+            let rgba: RGBA = SRGB(color::srgb_from_hex(&hex).unwrap()).into();
+            prop_assert_eq!(color::hex_from_srgb(SRGB::from(rgba).into()), hex);
         }
     }
 }
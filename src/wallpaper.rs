@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: MPL-2.0-only
+
+use adw::gtk::{
+    gio::{self, prelude::*},
+    glib,
+};
+use palette::Lch;
+use std::path::{Path, PathBuf};
+
+use crate::util;
+
+pub const BACKGROUND_SCHEMA: &str = "org.gnome.desktop.background";
+const PICTURE_URI: &str = "picture-uri";
+/// added in GNOME 42
+const PICTURE_URI_DARK: &str = "picture-uri-dark";
+
+/// The GNOME background settings, `None` if the schema isn't installed
+pub fn background_settings() -> Option<gio::Settings> {
+    let schema = gio::SettingsSchemaSource::default()?.lookup(BACKGROUND_SCHEMA, true)?;
+    Some(gio::Settings::new_full(
+        &schema,
+        None::<&gio::SettingsBackend>,
+        None,
+    ))
+}
+
+/// Whether the schema has the dark wallpaper, reading a missing key aborts
+fn has_dark_wallpaper(settings: &gio::Settings) -> bool {
+    settings
+        .settings_schema()
+        .map(|s| s.has_key(PICTURE_URI_DARK))
+        .unwrap_or_default()
+}
+
+/// The wallpaper for the color scheme, the dark wallpaper falls back to the light one
+pub fn wallpaper_path(settings: &gio::Settings, is_dark: bool) -> Option<PathBuf> {
+    let mut uri = settings.string(PICTURE_URI);
+    if is_dark && has_dark_wallpaper(settings) {
+        let dark_uri = settings.string(PICTURE_URI_DARK);
+        if !dark_uri.is_empty() {
+            uri = dark_uri;
+        }
+    }
+    if uri.is_empty() {
+        return None;
+    }
+
+    let path = gio::File::for_uri(&uri).path()?;
+    // inside of a Flatpak sandbox, the wallpapers of the host system are below /run/host
+    let host_path = Path::new("/run/host").join(path.strip_prefix("/").ok()?);
+    if !path.exists() && host_path.exists() {
+        Some(host_path)
+    } else {
+        Some(path)
+    }
+}
+
+/// The best accent extracted from the wallpaper for the color scheme
+/// `None` if there is no wallpaper or it doesn't have any colorful areas
+pub fn wallpaper_accent(settings: &gio::Settings, is_dark: bool) -> anyhow::Result<Option<Lch>> {
+    match wallpaper_path(settings, is_dark) {
+        Some(path) => image_accent(&path),
+        None => Ok(None),
+    }
+}
+
+/// Like `wallpaper_accent`, but the image is decoded on a worker thread
+/// `f` is called with the result on the main context
+pub fn wallpaper_accent_async<F: FnOnce(anyhow::Result<Option<Lch>>) + 'static>(
+    settings: &gio::Settings,
+    is_dark: bool,
+    f: F,
+) {
+    let path = match wallpaper_path(settings, is_dark) {
+        Some(path) => path,
+        None => return f(Ok(None)),
+    };
+
+    let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+    std::thread::spawn(move || {
+        // the receiver is gone if the main loop quit in the meantime
+        let _ = sender.send(image_accent(&path));
+    });
+    let mut f = Some(f);
+    receiver.attach(None, move |res| {
+        if let Some(f) = f.take() {
+            f(res);
+        }
+        glib::Continue(false)
+    });
+}
+
+fn image_accent(path: &Path) -> anyhow::Result<Option<Lch>> {
+    Ok(util::extract_accents(path, 1)?.first().map(|c| c.color))
+}
+
+/// Calls `f` whenever the light or dark wallpaper changes, disconnect the handler to stop
+pub fn connect_wallpaper_changed<F: Fn(&gio::Settings) + 'static>(
+    settings: &gio::Settings,
+    f: F,
+) -> glib::SignalHandlerId {
+    settings.connect_changed(None, move |settings, key| {
+        if key == PICTURE_URI || key == PICTURE_URI_DARK {
+            f(settings);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adw::gtk::gdk_pixbuf::{Colorspace, Pixbuf};
+    use std::{cell::Cell, rc::Rc};
+
+    /// the keys of the background schema that are used, `picture-uri-dark` is new in GNOME 42
+    fn schema_xml(with_dark: bool) -> String {
+        let dark = if with_dark {
+            format!("<key name=\"{PICTURE_URI_DARK}\" type=\"s\"><default>''</default></key>")
+        } else {
+            String::new()
+        };
+        format!(
+            "<schemalist>\
+               <schema id=\"{BACKGROUND_SCHEMA}\" path=\"/org/gnome/desktop/background/\">\
+                 <key name=\"{PICTURE_URI}\" type=\"s\"><default>''</default></key>\
+                 {dark}\
+                 <key name=\"picture-options\" type=\"s\"><default>'zoom'</default></key>\
+               </schema>\
+             </schemalist>"
+        )
+    }
+
+    /// settings kept in memory with a schema compiled for the test,
+    /// so the tests neither need the GNOME schemas nor change the wallpaper of the session
+    fn memory_settings(name: &str, with_dark: bool) -> gio::Settings {
+        let dir = temp_dir(&format!("{name}-schema"));
+        std::fs::write(
+            dir.join(format!("{BACKGROUND_SCHEMA}.gschema.xml")),
+            schema_xml(with_dark),
+        )
+        .unwrap();
+        let status = std::process::Command::new("glib-compile-schemas")
+            .arg(&dir)
+            .status()
+            .expect("Failed to run glib-compile-schemas, which comes with the GLib build tools");
+        assert!(status.success());
+
+        let schema = gio::SettingsSchemaSource::from_directory(&dir, None, false)
+            .unwrap()
+            .lookup(BACKGROUND_SCHEMA, false)
+            .unwrap();
+        gio::Settings::new_full(&schema, Some(&gio::memory_settings_backend_new()), None)
+    }
+
+    /// writes a single color image and returns its URI
+    fn wallpaper(dir: &Path, name: &str, rgba: u32) -> String {
+        let pixbuf = Pixbuf::new(Colorspace::Rgb, false, 8, 32, 32).unwrap();
+        pixbuf.fill(rgba);
+        let path = dir.join(name);
+        pixbuf.savev(&path, "png", &[]).unwrap();
+        gio::File::for_path(&path).uri().to_string()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hue-chroma-accent-{name}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn accent_follows_the_color_scheme() {
+        let settings = memory_settings("wallpaper-scheme", true);
+        let dir = temp_dir("wallpaper-scheme");
+        let blue = wallpaper(&dir, "blue.png", 0x3584e4ff);
+        let orange = wallpaper(&dir, "orange.png", 0xff7800ff);
+        settings.set_string(PICTURE_URI, &blue).unwrap();
+        settings.set_string(PICTURE_URI_DARK, &orange).unwrap();
+
+        let light = wallpaper_accent(&settings, false).unwrap().unwrap();
+        let dark = wallpaper_accent(&settings, true).unwrap().unwrap();
+        assert!((light.hue.to_positive_degrees() - 280.0).abs() < 15.0);
+        assert!((dark.hue.to_positive_degrees() - 60.0).abs() < 15.0);
+
+        // the dark wallpaper falls back to the light one
+        settings.set_string(PICTURE_URI_DARK, "").unwrap();
+        assert_eq!(
+            wallpaper_path(&settings, true),
+            wallpaper_path(&settings, false)
+        );
+    }
+
+    #[test]
+    fn dark_wallpaper_is_optional() {
+        let settings = memory_settings("wallpaper-gnome-41", false);
+        assert!(!has_dark_wallpaper(&settings));
+        let dir = temp_dir("wallpaper-gnome-41");
+        let blue = wallpaper(&dir, "blue.png", 0x3584e4ff);
+        settings.set_string(PICTURE_URI, &blue).unwrap();
+
+        assert!(wallpaper_path(&settings, true).is_some());
+        assert_eq!(
+            wallpaper_path(&settings, true),
+            wallpaper_path(&settings, false)
+        );
+    }
+
+    #[test]
+    fn gray_wallpaper_has_no_accent() {
+        let settings = memory_settings("wallpaper-gray", true);
+        let dir = temp_dir("wallpaper-gray");
+        let gray = wallpaper(&dir, "gray.png", 0x808080ff);
+        settings.set_string(PICTURE_URI, &gray).unwrap();
+
+        assert_eq!(wallpaper_accent(&settings, false).unwrap(), None);
+    }
+
+    #[test]
+    fn changes_are_reported() {
+        let settings = memory_settings("wallpaper-changes", true);
+        let changes = Rc::new(Cell::new(0));
+        let handler = connect_wallpaper_changed(
+            &settings,
+            glib::clone!(@strong changes => move |_| changes.set(changes.get() + 1)),
+        );
+
+        settings.set_string(PICTURE_URI, "file:///a.png").unwrap();
+        settings
+            .set_string(PICTURE_URI_DARK, "file:///b.png")
+            .unwrap();
+        settings.set_string("picture-options", "zoom").unwrap();
+        while glib::MainContext::default().iteration(false) {}
+        assert_eq!(changes.get(), 2);
+
+        settings.disconnect(handler);
+        settings.set_string(PICTURE_URI, "file:///c.png").unwrap();
+        while glib::MainContext::default().iteration(false) {}
+        assert_eq!(changes.get(), 2);
+    }
+}